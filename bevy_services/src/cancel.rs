@@ -17,7 +17,7 @@
 
 use bevy::prelude::Entity;
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 /// Response type that gets sent when a cancellation occurs.
 #[derive(Debug)]
@@ -91,6 +91,79 @@ pub enum CancellationCause {
     /// Note that if all of the inputs for a race are disposed instead of
     /// cancelled, then the race will be disposed and not cancelled.
     RaceCancelled(RaceCancelled),
+
+    /// A chain built with [`Dangling::cancel_with`](crate::Dangling::cancel_with) was
+    /// cancelled because its competing "canceller" chain produced a value
+    /// first. The entity provided is the node of the canceller chain that
+    /// won the race.
+    CancelledBy(CancelledBy),
+
+    /// A chain built with [`Dangling::timeout`](crate::Dangling::timeout) or
+    /// [`PromiseCommands::timeout`](crate::PromiseCommands::timeout) was
+    /// cancelled because its deadline elapsed before the chain completed.
+    Timeout(Timeout),
+
+    /// A service built with `.load_shed()` rejected a request immediately
+    /// because it was already at its admission limit, rather than queuing it.
+    LoadShed(LoadShed),
+
+    /// A service built with `.access(policy)` denied a request because the
+    /// caller's identity did not satisfy the policy.
+    AccessDenied(AccessDenied),
+}
+
+/// A description of what triggered a [`CancellationCause::CancelledBy`].
+#[derive(Debug, Clone)]
+pub struct CancelledBy {
+    /// The node of the canceller chain that produced a value first.
+    pub canceller_node: Entity,
+}
+
+impl From<CancelledBy> for CancellationCause {
+    fn from(value: CancelledBy) -> Self {
+        CancellationCause::CancelledBy(value)
+    }
+}
+
+/// A description of what triggered a [`CancellationCause::Timeout`].
+#[derive(Debug, Clone)]
+pub struct Timeout {
+    /// The node of the timer that elapsed.
+    pub at_node: Entity,
+    /// How much time had elapsed since the timer was started.
+    pub elapsed: Duration,
+}
+
+impl From<Timeout> for CancellationCause {
+    fn from(value: Timeout) -> Self {
+        CancellationCause::Timeout(value)
+    }
+}
+
+/// A description of what triggered a [`CancellationCause::LoadShed`].
+#[derive(Debug, Clone)]
+pub struct LoadShed {
+    /// The service that was already at its admission limit
+    pub service: Entity,
+}
+
+impl From<LoadShed> for CancellationCause {
+    fn from(value: LoadShed) -> Self {
+        CancellationCause::LoadShed(value)
+    }
+}
+
+/// A description of what triggered a [`CancellationCause::AccessDenied`].
+#[derive(Debug, Clone)]
+pub struct AccessDenied {
+    /// The service whose access policy denied the request
+    pub service: Entity,
+}
+
+impl From<AccessDenied> for CancellationCause {
+    fn from(value: AccessDenied) -> Self {
+        CancellationCause::AccessDenied(value)
+    }
 }
 
 #[derive(Debug)]
@@ -196,4 +269,33 @@ impl Cancel {
     pub fn filtered(source: Entity) -> Self {
         Self::new(source, CancellationCause::Filtered(source))
     }
+
+    /// Create a cancellation operation for a chain that lost a
+    /// [`Dangling::cancel_with`](crate::Dangling::cancel_with) race to its
+    /// canceller chain.
+    pub fn cancelled_by(apply_to: Entity, canceller_node: Entity) -> Self {
+        Self::new(apply_to, CancelledBy { canceller_node }.into())
+    }
+
+    /// Create a cancellation operation for a chain whose
+    /// [`Dangling::timeout`](crate::Dangling::timeout) or
+    /// [`PromiseCommands::timeout`](crate::PromiseCommands::timeout) deadline
+    /// elapsed.
+    pub fn timeout(apply_to: Entity, at_node: Entity, elapsed: Duration) -> Self {
+        Self::new(apply_to, Timeout { at_node, elapsed }.into())
+    }
+
+    /// Create a cancellation operation for a request that a `.load_shed()`
+    /// service rejected immediately because it was already at its admission
+    /// limit.
+    pub fn load_shed(apply_to: Entity, service: Entity) -> Self {
+        Self::new(apply_to, LoadShed { service }.into())
+    }
+
+    /// Create a cancellation operation for a request that a `.access(policy)`
+    /// service denied because the caller's identity did not satisfy the
+    /// policy.
+    pub fn access_denied(apply_to: Entity, service: Entity) -> Self {
+        Self::new(apply_to, AccessDenied { service }.into())
+    }
 }
@@ -15,12 +15,17 @@
  *
 */
 
-use crate::{Chain, OutputChain, ModifiersClosed, ModifiersUnset, UnusedTarget};
+use crate::{
+    Chain, OutputChain, ModifiersClosed, ModifiersUnset, UnusedTarget, PerformOperation,
+    Operation, ServeCmd, InputStorage, Cancel, DeadlineTimer, TimeoutGuard,
+};
 
-use bevy::prelude::{Entity, Commands};
+use bevy::prelude::{Entity, Commands, Component};
 
 use smallvec::SmallVec;
 
+use std::time::{Duration, Instant};
+
 /// While building a [`Chain`] you may need to pause building the chain and
 /// resume chaining later. You can also zip multiple [`Dangling`] instances
 /// together with a tuple and join or race them.
@@ -46,6 +51,65 @@ impl<Response: 'static + Send + Sync, Streams> Dangling<Response, Streams> {
     pub(crate) fn new(source: Entity, target: Entity) -> Self {
         Self { source, target, _ignore: Default::default() }
     }
+
+    /// Borrowing the `cancel_with` idea from the kyansel crate: race this
+    /// chain against a `trigger` chain. Whichever one produces a value first
+    /// wins. If this chain wins, its value is forwarded downstream as normal.
+    /// If `trigger` wins first, this chain is cancelled with
+    /// [`CancellationCause::CancelledBy`](crate::CancellationCause::CancelledBy)
+    /// and `trigger`'s value is discarded.
+    ///
+    /// This lets you say "run this until a shutdown signal / competing event
+    /// fires" without hand-wiring a [`ZippedChains::race`] plus a manual
+    /// [`Chain::sever`](crate::Chain::sever) of the loser. Concretely this
+    /// funnels both `self` and `trigger` into a single arbiter node that
+    /// forwards the first arrival downstream and issues a
+    /// [`Cancel`](crate::Cancel) to the losing branch, with the cancellation
+    /// cascading up the dependency chain the same way the
+    /// [`ZippedChains::race`] docs describe.
+    pub fn cancel_with<'w, 's, 'a, Trigger, TriggerStreams>(
+        self,
+        trigger: Dangling<Trigger, TriggerStreams>,
+        commands: &'a mut Commands<'w, 's>,
+    ) -> OutputChain<'w, 's, 'a, Response>
+    where
+        Trigger: 'static + Send + Sync,
+    {
+        let arbiter = commands.spawn(RaceArbiter::default()).id();
+
+        commands.add(PerformOperation::new(
+            self.target,
+            RaceBranch::<Response>::new(arbiter, trigger.source),
+        ));
+        commands.add(PerformOperation::new(
+            trigger.target,
+            CancelTrigger::<Trigger>::new(arbiter, self.source),
+        ));
+
+        Chain::new(self.source, self.target, commands)
+    }
+
+    /// Borrowing the same ergonomics kyansel gets from racing a future
+    /// against a `Delay`: cancel this chain if it has not completed by
+    /// `duration` after this method is called. Internally a timer node is
+    /// spawned alongside `self`; once the deadline elapses,
+    /// [`check_expired_deadlines`](crate::check_expired_deadlines) cancels
+    /// this chain with
+    /// [`CancellationCause::Timeout`](crate::CancellationCause::Timeout).
+    /// If `self` finishes first, a [`TimeoutGuard`] installed on `self.target`
+    /// despawns the timer immediately so it never fires spuriously.
+    pub fn timeout<'w, 's, 'a>(
+        self,
+        duration: Duration,
+        commands: &'a mut Commands<'w, 's>,
+    ) -> OutputChain<'w, 's, 'a, Response> {
+        let timer = commands.spawn(DeadlineTimer {
+            at: Instant::now() + duration,
+            guarded: self.target,
+        }).id();
+        commands.add(PerformOperation::new(self.target, TimeoutGuard::<Response>::new(timer)));
+        Chain::new(self.source, self.target, commands)
+    }
 }
 
 /// This trait is for [`Dangling`] [`Chains`](Chain) that are "zipped" together in a tuple. The
@@ -81,6 +145,70 @@ pub trait ZippedChains {
         Self: Sized;
 }
 
+/// Which half of a [`ZippedChains::join`] pair a [`JoinBranch2`] is feeding.
+enum JoinSlot {
+    Left,
+    Right,
+}
+
+/// Component on the gather entity for a [`ZippedChains::join`] of two
+/// [`Dangling`] chains. Buffers whichever branch arrives first and, once both
+/// have arrived for the session, emits the zipped tuple to `target`.
+#[derive(Component)]
+struct JoinStorage2<A, B> {
+    a: Option<A>,
+    b: Option<B>,
+    target: Entity,
+}
+
+impl<A, B> JoinStorage2<A, B> {
+    fn new(target: Entity) -> Self {
+        Self { a: None, b: None, target }
+    }
+}
+
+/// Operation inserted at one branch's dangling target for
+/// [`ZippedChains::join`]: feeds the branch's response into the shared
+/// [`JoinStorage2`] on `gather`, which forwards the zipped tuple once every
+/// branch has reported in.
+struct JoinBranch2<A, B> {
+    gather: Entity,
+    slot: JoinSlot,
+    _ignore: std::marker::PhantomData<(A, B)>,
+}
+
+impl<A, B> JoinBranch2<A, B> {
+    fn new(gather: Entity, slot: JoinSlot) -> Self {
+        Self { gather, slot, _ignore: Default::default() }
+    }
+}
+
+impl<A: 'static + Send + Sync, B: 'static + Send + Sync> Operation for JoinBranch2<A, B> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        match self.slot {
+            JoinSlot::Left => {
+                let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<A>>() else { return };
+                let Some(mut storage) = world.get_mut::<JoinStorage2<A, B>>(self.gather) else { return };
+                storage.a = Some(value);
+            }
+            JoinSlot::Right => {
+                let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<B>>() else { return };
+                let Some(mut storage) = world.get_mut::<JoinStorage2<A, B>>(self.gather) else { return };
+                storage.b = Some(value);
+            }
+        }
+
+        let Some(mut storage) = world.get_mut::<JoinStorage2<A, B>>(self.gather) else { return };
+        if storage.a.is_none() || storage.b.is_none() {
+            return;
+        }
+
+        let (a, b, target) = (storage.a.take().unwrap(), storage.b.take().unwrap(), storage.target);
+        world.entity_mut(target).insert(InputStorage((a, b)));
+        roster.queue(target);
+    }
+}
+
 impl<A, StreamsA, B, StreamsB> ZippedChains for (Dangling<A, StreamsA>, Dangling<B, StreamsB>)
 where
     A: 'static + Send + Sync,
@@ -91,11 +219,21 @@ where
         self,
         commands: &'a mut Commands<'w, 's>
     ) -> OutputChain<'w, 's, 'a, Self::JoinedResponse> {
-        // FIXME TODO(@mxgrey): Actually implement something here. This is just
-        // a placeholder to test the API for now.
-        let source = commands.spawn(()).id();
+        let (dangle_a, dangle_b) = self;
+        let gather = commands.spawn(()).id();
         let target = commands.spawn(UnusedTarget).id();
-        Chain::new(source, target, commands)
+        commands.entity(gather).insert(JoinStorage2::<A, B>::new(target));
+
+        commands.add(PerformOperation::new(
+            dangle_a.target,
+            JoinBranch2::<A, B>::new(gather, JoinSlot::Left),
+        ));
+        commands.add(PerformOperation::new(
+            dangle_b.target,
+            JoinBranch2::<A, B>::new(gather, JoinSlot::Right),
+        ));
+
+        Chain::new(gather, target, commands)
     }
 
     fn race<'w, 's, 'a, Builders: RaceBuilders<'w, 's, Self>>(
@@ -118,6 +256,94 @@ pub trait RaceBuilders<'w, 's, Z> {
     fn apply_race_builders<'a>(self, zip: Z, commands: &'a mut Commands<'w, 's>) -> Self::Output;
 }
 
+/// Shared coordination component for a race between [`Dangling`] chains:
+/// whichever branch reports in first flips `decided`, records itself as
+/// `winner`, and cancels the losing branch(es), so at most one of the
+/// branches is ever allowed to forward its response.
+#[derive(Component, Default)]
+struct RaceArbiter {
+    decided: bool,
+    winner: Option<Entity>,
+}
+
+/// Operation inserted at one branch's dangling target for a race between
+/// differently-typed [`Dangling`] chains: if `arbiter` has not already been
+/// decided, forwards the response as normal through the builder's own chain
+/// and cancels `other` (the losing branch's source) with a
+/// [`CancellationCause::CancelledBy`](crate::CancellationCause::CancelledBy)
+/// naming this branch. A response that arrives after the decision is not
+/// forwarded; this branch is cancelled the same way instead.
+struct RaceBranch<T> {
+    arbiter: Entity,
+    other: Entity,
+    _ignore: std::marker::PhantomData<T>,
+}
+
+impl<T> RaceBranch<T> {
+    fn new(arbiter: Entity, other: Entity) -> Self {
+        Self { arbiter, other, _ignore: Default::default() }
+    }
+}
+
+impl<T: 'static + Send + Sync> Operation for RaceBranch<T> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<T>>() else { return };
+
+        let Some(mut arbiter) = world.get_mut::<RaceArbiter>(self.arbiter) else { return };
+        if arbiter.decided {
+            // Some other branch already won the race; this branch's value
+            // arrived too late, so it gets cancelled just like the loser
+            // does when this branch is the one that wins.
+            roster.cancel(Cancel::cancelled_by(source, arbiter.winner.unwrap_or(self.arbiter)));
+            return;
+        }
+        arbiter.decided = true;
+        arbiter.winner = Some(source);
+
+        roster.cancel(Cancel::cancelled_by(self.other, source));
+
+        // Let the response continue down the builder's own chain exactly as
+        // if it had arrived here with no race involved.
+        world.entity_mut(source).insert(InputStorage(value));
+        roster.queue(source);
+    }
+}
+
+/// Operation inserted at a [`Dangling::cancel_with`] trigger chain's
+/// dangling target: the trigger's own value is never forwarded anywhere, it
+/// exists purely to signal "cancel the guarded chain now". If `arbiter` has
+/// not already been decided by the guarded chain completing first, this
+/// cancels `other` (the guarded chain's source) with
+/// [`CancellationCause::CancelledBy`](crate::CancellationCause::CancelledBy).
+struct CancelTrigger<T> {
+    arbiter: Entity,
+    other: Entity,
+    _ignore: std::marker::PhantomData<T>,
+}
+
+impl<T> CancelTrigger<T> {
+    fn new(arbiter: Entity, other: Entity) -> Self {
+        Self { arbiter, other, _ignore: Default::default() }
+    }
+}
+
+impl<T: 'static + Send + Sync> Operation for CancelTrigger<T> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        // The trigger only ever signals a cancellation; its delivered value
+        // has nowhere downstream to go.
+        let _ = world.entity_mut(source).remove::<InputStorage<T>>();
+
+        let Some(mut arbiter) = world.get_mut::<RaceArbiter>(self.arbiter) else { return };
+        if arbiter.decided {
+            return;
+        }
+        arbiter.decided = true;
+        arbiter.winner = Some(source);
+
+        roster.cancel(Cancel::cancelled_by(self.other, source));
+    }
+}
+
 impl<'w, 's, A, StreamsA, Fa, Ua, B, StreamsB, Fb, Ub> RaceBuilders<'w, 's, (Dangling<A, StreamsA>, Dangling<B, StreamsB>)> for (Fa, Fb)
 where
     A: 'static + Send + Sync,
@@ -132,9 +358,17 @@ where
         commands: &'a mut Commands<'w, 's>
     ) -> Self::Output {
         let (f_a, f_b) = self;
-        // FIXME TODO(@mxgrey): Funnel the dangles into a single target and then fan
-        // them out again to their individual handlers. The current implementation
-        // is a temporary short-cut for proof of concept.
+        let arbiter = commands.spawn(RaceArbiter::default()).id();
+
+        commands.add(PerformOperation::new(
+            dangle_a.target,
+            RaceBranch::<A>::new(arbiter, dangle_b.source),
+        ));
+        commands.add(PerformOperation::new(
+            dangle_b.target,
+            RaceBranch::<B>::new(arbiter, dangle_a.source),
+        ));
+
         let u_a = (f_a)(Chain::new(dangle_a.source, dangle_a.target, commands));
         let u_b = (f_b)(Chain::new(dangle_b.source, dangle_b.target, commands));
         (u_a, u_b)
@@ -182,6 +416,97 @@ pub trait BundledChains {
     ) -> Chain<'w, 's, 'a, Self::Response, (), ModifiersUnset>;
 }
 
+/// Component on the gather entity for a [`BundledChains::join`]. Buffers a
+/// response into `slots[index]` for each branch that reports in and, once
+/// every slot is filled, emits the collected bundle to `target`.
+#[derive(Component)]
+struct JoinStorageN<Response> {
+    slots: Vec<Option<Response>>,
+    target: Entity,
+}
+
+impl<Response> JoinStorageN<Response> {
+    fn new(count: usize, target: Entity) -> Self {
+        let mut slots = Vec::with_capacity(count);
+        slots.resize_with(count, || None);
+        Self { slots, target }
+    }
+}
+
+/// Operation inserted at one branch's dangling target for a
+/// [`BundledChains::join`]: feeds the branch's response into its `index` slot
+/// of the shared [`JoinStorageN`] on `gather`, which forwards the full bundle
+/// once every branch has reported in.
+struct JoinBranchN<Response> {
+    gather: Entity,
+    index: usize,
+    _ignore: std::marker::PhantomData<Response>,
+}
+
+impl<Response> JoinBranchN<Response> {
+    fn new(gather: Entity, index: usize) -> Self {
+        Self { gather, index, _ignore: Default::default() }
+    }
+}
+
+impl<Response: 'static + Send + Sync> Operation for JoinBranchN<Response> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else { return };
+
+        let Some(mut storage) = world.get_mut::<JoinStorageN<Response>>(self.gather) else { return };
+        let Some(slot) = storage.slots.get_mut(self.index) else { return };
+        *slot = Some(value);
+
+        if storage.slots.iter().any(Option::is_none) {
+            return;
+        }
+
+        let bundle: SmallVec<[Response; 8]> = storage.slots.iter_mut()
+            .map(|slot| slot.take().unwrap())
+            .collect();
+        let target = storage.target;
+        world.entity_mut(target).insert(InputStorage(bundle));
+        roster.queue(target);
+    }
+}
+
+/// Operation inserted at one branch's dangling target for a
+/// [`BundledChains::race`]: forwards the first branch's response to `target`,
+/// guarded by the shared [`RaceArbiter`] on `arbiter` so only the winner's
+/// response is ever delivered. A branch whose response arrives after the
+/// race is decided is cancelled at that point; branches that are still
+/// running when the winner arrives are not cancelled until they report in.
+struct RaceBranchN<Response> {
+    arbiter: Entity,
+    target: Entity,
+    _ignore: std::marker::PhantomData<Response>,
+}
+
+impl<Response> RaceBranchN<Response> {
+    fn new(arbiter: Entity, target: Entity) -> Self {
+        Self { arbiter, target, _ignore: Default::default() }
+    }
+}
+
+impl<Response: 'static + Send + Sync> Operation for RaceBranchN<Response> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else { return };
+
+        let Some(mut arbiter) = world.get_mut::<RaceArbiter>(self.arbiter) else { return };
+        if arbiter.decided {
+            // Some other branch already won; cancel this one the same way
+            // every other loser of the race gets cancelled.
+            roster.cancel(Cancel::cancelled_by(source, arbiter.winner.unwrap_or(self.arbiter)));
+            return;
+        }
+        arbiter.decided = true;
+        arbiter.winner = Some(source);
+
+        world.entity_mut(self.target).insert(InputStorage(value));
+        roster.queue(self.target);
+    }
+}
+
 impl<Response, Streams, T> BundledChains for T
 where
     Response: 'static + Send + Sync,
@@ -192,15 +517,35 @@ where
         self,
         commands: &'a mut Commands<'w, 's>,
     ) -> Chain<'w, 's, 'a, SmallVec<[Self::Response; 8]>, (), ModifiersUnset> {
-        // FIXME TODO(@mxgrey): Funnel the dangling chains into one target
-        Chain::new(commands.spawn(()).id(), commands.spawn(()).id(), commands)
+        let branches: SmallVec<[Dangling<Response, Streams>; 8]> = self.into_iter().collect();
+        let gather = commands.spawn(()).id();
+        let target = commands.spawn(UnusedTarget).id();
+        commands.entity(gather).insert(JoinStorageN::<Response>::new(branches.len(), target));
+
+        for (index, branch) in branches.into_iter().enumerate() {
+            commands.add(PerformOperation::new(
+                branch.target,
+                JoinBranchN::<Response>::new(gather, index),
+            ));
+        }
+
+        Chain::new(gather, target, commands)
     }
 
     fn race<'w, 's, 'a>(
         self,
         commands: &'a mut Commands<'w, 's>,
     ) -> Chain<'w, 's, 'a, Self::Response, (), ModifiersUnset> {
-        // FIXME TODO(@mxgrey): Funnal the races into one target
-        Chain::new(commands.spawn(()).id(), commands.spawn(()).id(), commands)
+        let arbiter = commands.spawn(RaceArbiter::default()).id();
+        let target = commands.spawn(UnusedTarget).id();
+
+        for branch in self.into_iter() {
+            commands.add(PerformOperation::new(
+                branch.target,
+                RaceBranchN::<Response>::new(arbiter, target),
+            ));
+        }
+
+        Chain::new(arbiter, target, commands)
     }
 }
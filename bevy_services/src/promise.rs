@@ -17,15 +17,23 @@
 
 use crate::{
     UnusedTarget, Terminate, PerformOperation,
-    Fork, Chosen, ApplyLabel, Stream, Provider,
-    AsMap, IntoBlockingMap, IntoAsyncMap,
+    Fork, Chosen, ApplyLabel, Stream,
+    AsMap, IntoBlockingMap, IntoAsyncMap, DeadlineTimer, TimeoutGuard,
+    Operation, ServeCmd, InputStorage, Cancel, RetryPolicy, Cancelled, OperationRoster,
 };
 
-use bevy::prelude::{Entity, Commands};
+use futures::task::noop_waker;
+
+use bevy::prelude::{Entity, Commands, Component, Query, App, Update, World};
+use bevy::ecs::system::{CommandQueue, SystemState};
+use bevy::ecs::world::Mut;
+use bevy::tasks::AsyncComputeTaskPool;
 
 use std::{
-    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
-    future::Future, task::{Context, Poll}, pin::Pin
+    sync::{Arc, Mutex, Weak, atomic::{AtomicBool, Ordering}},
+    future::Future, task::{Context, Poll}, pin::Pin,
+    time::{Duration, Instant},
+    marker::PhantomData,
 };
 
 pub(crate) mod private;
@@ -101,6 +109,24 @@ impl<T> Promise<T> {
         self
     }
 
+    /// Wait for the promise to be resolved, but give up and stop waiting if
+    /// `timeout` elapses first, instead of blocking indefinitely like
+    /// [`Self::wait_mut`]. The internal state is updated the same way
+    /// [`Self::wait_mut`] updates it, so if the deadline elapses before a
+    /// result arrives, the returned state will simply still be
+    /// [`PromiseState::Pending`].
+    pub fn wait_timeout(&mut self, timeout: Duration) -> PromiseState<&T> {
+        if !self.state.is_pending() {
+            return self.state.as_ref();
+        }
+
+        if let Some(mut guard) = Self::impl_wait_timeout(&self.target, timeout, None) {
+            Self::impl_try_take_result(&mut self.state, &mut guard.result);
+        }
+
+        self.state.as_ref()
+    }
+
     /// Wait for the promise to be resolved and update the internal state with
     /// the result.
     pub fn wait_mut(&mut self) -> &mut Self {
@@ -166,6 +192,135 @@ impl<T> Promise<T> {
     }
 }
 
+impl<T: 'static + Send + Sync + Unpin> Promise<T> {
+    /// Resolve once every promise in `promises` has delivered a value,
+    /// collecting them in the same order as `promises`. If any of the
+    /// inputs is canceled (or has already been taken), the aggregate is
+    /// canceled too as soon as that is observed, instead of waiting on the
+    /// rest. An empty `promises` resolves immediately to an empty `Vec`.
+    pub fn join_all(promises: Vec<Promise<T>>) -> Promise<Vec<T>> {
+        let (aggregate, sender) = Promise::new();
+        if promises.is_empty() {
+            sender.send(Vec::new());
+            return aggregate;
+        }
+
+        let results = (0..promises.len()).map(|_| None).collect();
+        let joined = JoinAll {
+            promises: promises.into_iter().map(Some).collect(),
+            results,
+        };
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                if let Some(values) = joined.await {
+                    sender.send(values);
+                }
+                // If a value never arrives above, `sender` is simply dropped
+                // here without being used, which cancels `aggregate` the same
+                // way any other undelivered [`Promise`] gets canceled when
+                // its sending half disappears.
+            })
+            .detach();
+
+        aggregate
+    }
+
+    /// Resolve as soon as the first promise in `promises` delivers a value,
+    /// dropping the rest, which cancels them the same way any other
+    /// [`Promise`] is canceled when it is dropped while still pending. Only
+    /// one winner is ever produced, even if more than one input becomes
+    /// available in the same poll. An empty `promises` is canceled right
+    /// away, since its sender is dropped without ever sending.
+    pub fn race(promises: Vec<Promise<T>>) -> Promise<T> {
+        let (aggregate, sender) = Promise::new();
+        if promises.is_empty() {
+            return aggregate;
+        }
+
+        let race = Race {
+            promises: promises.into_iter().map(Some).collect(),
+        };
+
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                if let Some(value) = race.await {
+                    sender.send(value);
+                }
+            })
+            .detach();
+
+        aggregate
+    }
+}
+
+/// Drives [`Promise::join_all`] by polling each input promise and collecting
+/// its result in place; resolves to `None` as soon as any input turns out to
+/// be anything other than [`PromiseState::Available`].
+struct JoinAll<T> {
+    promises: Vec<Option<Promise<T>>>,
+    results: Vec<Option<T>>,
+}
+
+impl<T: Unpin> Future for JoinAll<T> {
+    type Output = Option<Vec<T>>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut all_ready = true;
+        for (index, slot) in this.promises.iter_mut().enumerate() {
+            if let Some(promise) = slot {
+                match Pin::new(promise).poll(cx) {
+                    Poll::Ready(PromiseState::Available(value)) => {
+                        this.results[index] = Some(value);
+                        *slot = None;
+                    }
+                    Poll::Ready(_) => {
+                        return Poll::Ready(None);
+                    }
+                    Poll::Pending => {
+                        all_ready = false;
+                    }
+                }
+            }
+        }
+
+        if all_ready {
+            Poll::Ready(Some(this.results.iter_mut().map(|r| r.take().unwrap()).collect()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Drives [`Promise::race`] by polling each input promise in order and
+/// resolving with the first one to become [`PromiseState::Available`]; the
+/// rest are left in `promises` to be dropped (and thereby canceled) once
+/// this future is dropped.
+struct Race<T> {
+    promises: Vec<Option<Promise<T>>>,
+}
+
+impl<T: Unpin> Future for Race<T> {
+    type Output = Option<T>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        for slot in this.promises.iter_mut() {
+            if let Some(promise) = slot {
+                if let Poll::Ready(state) = Pin::new(promise).poll(cx) {
+                    if let PromiseState::Available(value) = state {
+                        return Poll::Ready(Some(value));
+                    }
+                    // This input lost the race without winning it outright
+                    // (canceled or already taken); drop it and keep polling
+                    // whichever inputs remain.
+                    *slot = None;
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
 impl<T> Drop for Promise<T> {
     fn drop(&mut self) {
         if self.state.is_pending() {
@@ -291,6 +446,23 @@ impl Interrupter {
             waiter.interruptible.interrupt();
         }
         guard.waiters.clear();
+
+        let on_trigger: Vec<_> = guard.on_trigger.drain(..).collect();
+        let children: Vec<_> = guard.children.drain(..).collect();
+        drop(guard);
+
+        for f in on_trigger {
+            f();
+        }
+
+        // Recursively interrupt every live descendant so that interrupting a
+        // parent cascades down the whole subtree, while interrupting a child
+        // never touches its parent or siblings.
+        for child in children {
+            if let Some(child) = child.upgrade() {
+                Interrupter { inner: child }.interrupt();
+            }
+        }
     }
 
     /// If interrupt() has been called on this Interrupter in the past, calling
@@ -308,10 +480,68 @@ impl Interrupter {
         }
     }
 
+    /// Create a child `Interrupter` scoped to a subtree of related requests:
+    /// calling [`Self::interrupt`] on the returned child never affects this
+    /// Interrupter or any of its other children, but calling it on `self` (or
+    /// any ancestor of `self`) recursively interrupts the child as well. This
+    /// is the same relationship as tokio's `CancellationToken::child_token`.
+    pub fn child_token(&self) -> Interrupter {
+        let child = Interrupter { inner: Arc::new(Mutex::new(InterrupterInner::new())) };
+
+        let mut guard = match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if guard.triggered {
+            // An already-triggered Interrupter will never call interrupt()
+            // again, so start the child in the triggered state directly
+            // instead of registering it as a child that would never hear
+            // about the trigger.
+            if let Ok(mut child_guard) = child.inner.lock() {
+                child_guard.triggered = true;
+            }
+            return child;
+        }
+
+        guard.children.push(Arc::downgrade(&child.inner));
+        drop(guard);
+
+        if let Ok(mut child_guard) = child.inner.lock() {
+            child_guard.parent = Some(Arc::downgrade(&self.inner));
+        }
+
+        child
+    }
+
+    /// A [`Promise`] that resolves as soon as this Interrupter fires, so
+    /// async code can `select!`/poll on cancellation instead of only being
+    /// able to block on it with [`Promise::interruptible_wait`].
+    pub fn cancelled(&self) -> Promise<()> {
+        let (promise, sender) = Promise::new();
+
+        let mut guard = match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if guard.triggered {
+            sender.send(());
+        } else {
+            guard.on_trigger.push(Box::new(move || { sender.send(()); }));
+        }
+
+        promise
+    }
+
     fn push<T: 'static>(
         &self,
         target: Arc<PromiseTarget<T>>
     ) -> Option<Arc<AtomicBool>> {
+        if self.is_triggered_by_ancestry() {
+            return None;
+        }
+
         let mut guard = match self.inner.lock() {
             Ok(guard) => guard,
             Err(poisoned) => {
@@ -334,6 +564,26 @@ impl Interrupter {
         guard.waiters.push(interruptee);
         Some(interrupt)
     }
+
+    /// Walk up the parent chain and check whether this Interrupter or any of
+    /// its ancestors has already been triggered. A freshly pushed waiter must
+    /// observe an ancestor's trigger immediately rather than waiting for a
+    /// trigger that will only ever propagate to its direct children.
+    fn is_triggered_by_ancestry(&self) -> bool {
+        let guard = match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if guard.triggered {
+            return true;
+        }
+
+        match guard.parent.as_ref().and_then(Weak::upgrade) {
+            Some(parent) => Interrupter { inner: parent }.is_triggered_by_ancestry(),
+            None => false,
+        }
+    }
 }
 
 impl Default for Interrupter {
@@ -391,6 +641,21 @@ pub type OnCancel<L> = Modifiers<L, Chosen>;
 /// All possible request modifiers have been chosen or can no longer be set.
 pub type ModifiersClosed = Modifiers<Chosen, Chosen>;
 
+/// Something that can be dispatched like a one-shot service request: given
+/// the entity whose [`InputStorage`] holds the upstream value (`source`) and
+/// a freshly spawned `target`, wire up whatever work is needed so that
+/// `target` eventually receives an [`InputStorage`] of this provider's
+/// `Response`. [`PromiseCommands::then`], [`PromiseCommands::map`],
+/// [`PromiseCommands::on_cancel_then`], and [`PromiseCommands::retry`] all
+/// build on this.
+pub trait Provider {
+    type Request;
+    type Response;
+    type Streams;
+
+    fn provide(self, source: Entity, target: Entity, commands: &mut Commands);
+}
+
 impl<'w, 's, 'a, Response: 'static + Send + Sync, Streams, L, C> PromiseCommands<'w, 's, 'a, Response, Streams, Modifiers<L, C>> {
     /// Have the service run until it is finished without holding onto any
     /// promise. Immediately after the service is finished, the storage for the
@@ -447,6 +712,77 @@ impl<'w, 's, 'a, Response: 'static + Send + Sync, Streams, L, C> PromiseCommands
         PromiseCommands::new(source, target, self.commands)
     }
 
+    /// Retry this step of the chain using `policy` to decide whether to
+    /// re-dispatch. Since a [`Provider`] is consumed by one call to
+    /// [`Provider::provide`], `make_provider` is called once per attempt to
+    /// build a fresh one. After each attempt's response arrives, `policy` is
+    /// given the chance to ask for another attempt (after a backoff) instead
+    /// of letting the response through, reusing the same [`RetryPolicy`]
+    /// that backs [`ServiceBuilder::retry`](crate::ServiceBuilder::retry).
+    ///
+    /// Backoffs are only driven forward synchronously (there is no sweep in
+    /// this crate that can poll a pending future to completion), so a
+    /// `policy` whose returned future is not immediately ready will have its
+    /// retry skipped and the pending response forwarded as-is.
+    pub fn retry<P, F, Pol>(
+        self,
+        make_provider: F,
+        policy: Pol,
+    ) -> PromiseCommands<'w, 's, 'a, Response, Streams, ModifiersUnset>
+    where
+        F: FnMut() -> P + 'static + Send + Sync,
+        P: Provider<Request = (), Response = Response, Streams = Streams>,
+        Pol: RetryPolicy<(), Response> + 'static + Send + Sync,
+    {
+        let final_target = self.commands.spawn(UnusedTarget).id();
+        let state = policy.create(&());
+        RetryDispatch::attempt(final_target, make_provider, policy, state, self.commands);
+        PromiseCommands::new(self.provider, final_target, self.commands)
+    }
+
+    /// Gate the request behind `limit`, parking it in arrival order if every
+    /// permit is already taken by another request sharing the same handle.
+    /// Unlike [`ServiceBuilder::concurrency_limit`](crate::ServiceBuilder::concurrency_limit),
+    /// which scopes its budget to a single service, a [`ConcurrencyLimit`]
+    /// handle can be cloned and passed to `.concurrency_limit(..)` calls on
+    /// any number of independent chains, and they will all share the same
+    /// pool of permits. The returned [`ConcurrencyPermit`] rides along with
+    /// the response and releases its permit (waking the next parked request,
+    /// once [`register_concurrency_sweep`] is running) as soon as it is
+    /// dropped, so consume it (e.g. with `.map_blocking(|(v, _permit)| v)`)
+    /// once you are done with whatever admission this permit was guarding.
+    pub fn concurrency_limit(
+        self,
+        limit: ConcurrencyLimit,
+    ) -> PromiseCommands<'w, 's, 'a, (Response, ConcurrencyPermit), Streams, ModifiersUnset> {
+        self.commands.add(PerformOperation::new(
+            self.target,
+            Gate::<ConcurrencyLimit, Response>::new(limit),
+        ));
+        PromiseCommands::new(self.provider, self.target, self.commands)
+    }
+
+    /// Gate the request behind `queue` the same way [`Self::concurrency_limit`]
+    /// gates it behind a [`ConcurrencyLimit`]: while fewer than `capacity`
+    /// requests sharing this handle hold a [`BufferSlot`], the request is
+    /// forwarded right away; otherwise it is parked until a slot frees up.
+    /// `queue` is a handle that can be shared across any number of chains,
+    /// so they all contend for the same slots. There is no bound on how many
+    /// requests can be parked, and a request is not canceled when
+    /// [`Buffer::is_full`] returns true. The returned [`BufferSlot`] releases
+    /// its slot when dropped, the same way [`ConcurrencyPermit`] does for
+    /// [`Self::concurrency_limit`].
+    pub fn buffer(
+        self,
+        queue: Buffer,
+    ) -> PromiseCommands<'w, 's, 'a, (Response, BufferSlot), Streams, ModifiersUnset> {
+        self.commands.add(PerformOperation::new(
+            self.target,
+            Gate::<Buffer, Response>::new(queue),
+        ));
+        PromiseCommands::new(self.provider, self.target, self.commands)
+    }
+
     /// Apply a one-time callback whose input is a [`BlockingMap`](crate::BlockingMap)
     /// or an [`AsyncMap`](crate::AsyncMap).
     pub fn map<M, F: AsMap<M>>(
@@ -530,6 +866,487 @@ impl<'w, 's, 'a, Response: 'static + Send + Sync, Streams, L, C> PromiseCommands
         let u = f(PromiseCommands::new(self.target, left_target, self.commands));
         (u, PromiseCommands::new(self.target, right_target, self.commands))
     }
+
+    /// Cancel the request if it has not been delivered within `duration`,
+    /// triggering the same cancellation path as any other cancellation,
+    /// including any [`Self::on_cancel`][crate::PromiseCommands::on_cancel]
+    /// chain installed upstream. Internally this spawns a dedicated timer
+    /// entity; once `duration` elapses without a response,
+    /// [`check_expired_deadlines`](crate::check_expired_deadlines) cancels
+    /// the request. If the request is delivered first, a [`TimeoutGuard`]
+    /// installed on `self.target` despawns the timer immediately so it never
+    /// fires spuriously.
+    pub fn timeout(
+        self,
+        duration: Duration,
+    ) -> PromiseCommands<'w, 's, 'a, Response, Streams, Modifiers<L, C>> {
+        let timer = self.commands.spawn(DeadlineTimer {
+            at: Instant::now() + duration,
+            guarded: self.target,
+        }).id();
+        self.commands.add(PerformOperation::new(self.target, TimeoutGuard::<Response>::new(timer)));
+        PromiseCommands::new(self.provider, self.target, self.commands)
+    }
+
+    /// Run `predicate` against the incoming value before letting the chain
+    /// continue; if it returns `Err`, the chain is canceled right here via
+    /// [`Cancel::filtered`](crate::Cancel::filtered) (routing into any
+    /// [`Self::on_cancel`] configured upstream) instead of forwarding the
+    /// value.
+    pub fn filter<E>(
+        self,
+        predicate: impl FnOnce(&Response) -> Result<(), E> + 'static + Send + Sync,
+    ) -> PromiseCommands<'w, 's, 'a, Response, Streams, Modifiers<L, C>>
+    where
+        Response: 'static + Send + Sync,
+        E: 'static + Send + Sync,
+    {
+        self.commands.add(PerformOperation::new(
+            self.target,
+            Filter::new(predicate),
+        ));
+        PromiseCommands::new(self.provider, self.target, self.commands)
+    }
+
+    /// Same as [`Self::filter`], but `predicate` returns a `Future` so that
+    /// validation which needs to perform IO (permission checks, resource
+    /// availability) can gate dispatch without blocking. The future is run
+    /// on the [`AsyncComputeTaskPool`](bevy::tasks::AsyncComputeTaskPool)
+    /// and polled once per update by [`check_pending_async_filters`] (via
+    /// [`register_async_filter_sweep`]) until it resolves.
+    pub fn filter_async<E, Task>(
+        self,
+        predicate: impl FnOnce(&Response) -> Task + 'static + Send + Sync,
+    ) -> PromiseCommands<'w, 's, 'a, Response, Streams, Modifiers<L, C>>
+    where
+        Response: 'static + Send + Sync,
+        Task: Future<Output = Result<(), E>> + 'static + Send + Sync,
+        E: 'static + Send + Sync,
+    {
+        self.commands.add(PerformOperation::new(
+            self.target,
+            FilterAsync::new(predicate),
+        ));
+        PromiseCommands::new(self.provider, self.target, self.commands)
+    }
+}
+
+/// Recorded on a request's target entity by [`PromiseCommands::filter`].
+/// Runs `predicate` against the incoming value before letting it continue
+/// downstream; a rejection cancels the chain via
+/// [`Cancel::filtered`](crate::Cancel::filtered) instead of forwarding the
+/// value.
+pub(crate) struct Filter<Response, E> {
+    predicate: Box<dyn FnOnce(&Response) -> Result<(), E> + Send + Sync>,
+}
+
+impl<Response, E> Filter<Response, E> {
+    fn new(predicate: impl FnOnce(&Response) -> Result<(), E> + 'static + Send + Sync) -> Self {
+        Self { predicate: Box::new(predicate) }
+    }
+}
+
+impl<Response: 'static + Send + Sync, E: 'static + Send + Sync> Operation for Filter<Response, E> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else { return };
+
+        if (self.predicate)(&value).is_err() {
+            roster.cancel(Cancel::filtered(source));
+            return;
+        }
+
+        world.entity_mut(source).insert(InputStorage(value));
+        roster.queue(source);
+    }
+}
+
+/// Recorded on a request's target entity by [`PromiseCommands::filter_async`].
+/// Same as [`Filter`], but `predicate` returns a `Future` that is run on the
+/// [`AsyncComputeTaskPool`](bevy::tasks::AsyncComputeTaskPool) before the
+/// request is allowed to continue.
+pub(crate) struct FilterAsync<Response, Task> {
+    predicate: Box<dyn FnOnce(&Response) -> Task + Send + Sync>,
+}
+
+impl<Response, Task> FilterAsync<Response, Task> {
+    fn new(predicate: impl FnOnce(&Response) -> Task + 'static + Send + Sync) -> Self {
+        Self { predicate: Box::new(predicate) }
+    }
+}
+
+impl<Response, E, Task> Operation for FilterAsync<Response, Task>
+where
+    Response: 'static + Send + Sync,
+    E: 'static + Send + Sync,
+    Task: Future<Output = Result<(), E>> + 'static + Send + Sync,
+{
+    fn execute(self: Box<Self>, ServeCmd { source, world, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else {
+            return;
+        };
+
+        let task = AsyncComputeTaskPool::get().spawn((self.predicate)(&value));
+        world.entity_mut(source).insert(PendingFilterAsync { value: Some(value), task });
+    }
+}
+
+/// Holds the original value and the in-flight [`FilterAsync`] predicate task
+/// on a request's target entity until the task resolves. Polled by
+/// [`check_pending_async_filters`].
+#[derive(Component)]
+pub(crate) struct PendingFilterAsync<Response, E> {
+    value: Option<Response>,
+    task: bevy::tasks::Task<Result<(), E>>,
+}
+
+/// Sweep every entity with a [`PendingFilterAsync`] from
+/// [`PromiseCommands::filter_async`] and, once its task resolves, either
+/// cancel the chain via [`Cancel::filtered`] (on `Err`) or forward the
+/// original value downstream (on `Ok`). Meant to run once per update, the
+/// same way [`check_expired_deadlines`](crate::check_expired_deadlines) does
+/// for `.timeout(~)`. Since [`PendingFilterAsync`] is generic, this must be
+/// registered once per concrete `Response`/`E` pair actually used with
+/// [`PromiseCommands::filter_async`] in the app, the same way Bevy requires
+/// any generic system to be.
+pub(crate) fn check_pending_async_filters<Response: 'static + Send + Sync, E: 'static + Send + Sync>(
+    world: &mut World,
+    roster: &mut OperationRoster,
+) {
+    let mut state: SystemState<Query<Entity, bevy::prelude::With<PendingFilterAsync<Response, E>>>> = SystemState::new(world);
+    let pending: Vec<Entity> = state.get(world).iter().collect();
+
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+
+    for source in pending {
+        let polled = match world.get_mut::<PendingFilterAsync<Response, E>>(source) {
+            Some(mut entry) => Pin::new(&mut entry.task).poll(&mut cx),
+            None => continue,
+        };
+
+        let Poll::Ready(result) = polled else {
+            continue;
+        };
+
+        let Some(PendingFilterAsync { value: Some(value), .. }) =
+            world.entity_mut(source).remove::<PendingFilterAsync<Response, E>>()
+        else {
+            continue;
+        };
+
+        if result.is_err() {
+            roster.cancel(Cancel::filtered(source));
+            continue;
+        }
+
+        world.entity_mut(source).insert(InputStorage(value));
+        roster.queue(source);
+    }
+}
+
+/// Register [`check_pending_async_filters`] for [`PromiseCommands::filter_async`]
+/// calls whose value/error types are `Response`/`E`, the same way
+/// [`register_timeout_sweep`](crate::register_timeout_sweep) does for
+/// `.timeout(~)`. Without this, a `.filter_async` predicate would spawn its
+/// task and then never be polled again.
+pub(crate) fn register_async_filter_sweep<Response: 'static + Send + Sync, E: 'static + Send + Sync>(
+    app: &mut App,
+) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            check_pending_async_filters::<Response, E>(world, &mut roster);
+        });
+    });
+}
+
+/// Installed by [`PromiseCommands::retry`] on the target of the attempt
+/// currently in flight. When that attempt's response arrives, consults its
+/// [`RetryPolicy`] and either dispatches another attempt (built fresh with
+/// `make_provider`, since a [`Provider`] is consumed by
+/// [`Provider::provide`]) or forwards the response to `final_target`.
+pub(crate) struct RetryDispatch<F, Response, Pol: RetryPolicy<(), Response>> {
+    final_target: Entity,
+    make_provider: F,
+    policy: Pol,
+    state: Pol::State,
+}
+
+impl<F, P, Pol, Response> RetryDispatch<F, Response, Pol>
+where
+    F: FnMut() -> P + 'static + Send + Sync,
+    P: Provider<Request = (), Response = Response, Streams = P::Streams>,
+    Pol: RetryPolicy<(), Response> + 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+{
+    pub(crate) fn attempt(
+        final_target: Entity,
+        mut make_provider: F,
+        policy: Pol,
+        state: Pol::State,
+        commands: &mut Commands,
+    ) {
+        let source = commands.spawn(UnusedTarget).id();
+        let attempt_target = commands.spawn(UnusedTarget).id();
+        make_provider().provide(source, attempt_target, commands);
+        commands.add(PerformOperation::new(
+            attempt_target,
+            Self { final_target, make_provider, policy, state },
+        ));
+    }
+}
+
+impl<F, P, Pol, Response> Operation for RetryDispatch<F, Response, Pol>
+where
+    F: FnMut() -> P + 'static + Send + Sync,
+    P: Provider<Request = (), Response = Response, Streams = P::Streams> + 'static,
+    Pol: RetryPolicy<(), Response> + 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+{
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Self { final_target, mut make_provider, mut policy, mut state } = *self;
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else {
+            return;
+        };
+
+        let outcome: Result<Response, Cancelled<()>> = Ok(value);
+        let backoff = policy.retry(&mut state, &outcome);
+        let Ok(value) = outcome else { unreachable!("RetryDispatch never produces an Err outcome") };
+
+        let ready_to_retry = match backoff {
+            None => false,
+            Some(mut delay) => {
+                let waker = noop_waker();
+                let mut cx = Context::from_waker(&waker);
+                matches!(Pin::new(&mut delay).poll(&mut cx), Poll::Ready(()))
+            }
+        };
+
+        if ready_to_retry {
+            let mut queue = CommandQueue::default();
+            {
+                let mut commands = Commands::new(&mut queue, world);
+                RetryDispatch::attempt(final_target, make_provider, policy, state, &mut commands);
+            }
+            queue.apply(world);
+            return;
+        }
+
+        world.entity_mut(final_target).insert(InputStorage(value));
+        roster.queue(final_target);
+    }
+}
+
+/// A bounded pool of admission slots shared by [`ConcurrencyLimit`] and
+/// [`Buffer`]: [`Self::try_acquire`] claims one if available, and
+/// [`Self::release`] (called automatically by [`Permit`]'s [`Drop`] impl)
+/// gives one back.
+pub trait Admit: Clone + 'static + Send + Sync {
+    fn try_acquire(&self) -> bool;
+    fn release(&self);
+}
+
+/// A shared pool of concurrency permits that any number of request chains
+/// can gate behind with [`PromiseCommands::concurrency_limit`]. Construct a
+/// handle with [`ConcurrencyLimit::new`] and clone it to share the same
+/// budget across chains: cloning duplicates the handle, not the underlying
+/// permit pool.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    inner: Arc<Mutex<usize>>,
+}
+
+impl ConcurrencyLimit {
+    /// Allow up to `n` requests across all chains sharing this handle to be
+    /// in flight at the same time.
+    pub fn new(n: usize) -> Self {
+        Self { inner: Arc::new(Mutex::new(n)) }
+    }
+}
+
+fn lock_or_recover<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+impl Admit for ConcurrencyLimit {
+    fn try_acquire(&self) -> bool {
+        let mut available = lock_or_recover(&self.inner);
+        if *available == 0 {
+            return false;
+        }
+        *available -= 1;
+        true
+    }
+
+    fn release(&self) {
+        *lock_or_recover(&self.inner) += 1;
+    }
+}
+
+/// A shared bounded queue that any number of request chains can park
+/// requests in with [`PromiseCommands::buffer`]. Like [`ConcurrencyLimit`],
+/// cloning a [`Buffer`] handle shares the same underlying queue rather than
+/// creating an independent one.
+#[derive(Clone)]
+pub struct Buffer {
+    inner: Arc<Mutex<BufferState>>,
+}
+
+struct BufferState {
+    capacity: usize,
+    queued: usize,
+}
+
+impl Buffer {
+    /// Allow up to `capacity` requests across all chains sharing this handle
+    /// to be queued at once.
+    pub fn new(capacity: usize) -> Self {
+        Self { inner: Arc::new(Mutex::new(BufferState { capacity, queued: 0 })) }
+    }
+
+    /// Whether the queue is already holding `capacity` requests, meaning a
+    /// further [`PromiseCommands::buffer`] call against this handle would
+    /// have to park its request behind all of these rather than dispatching
+    /// it right away.
+    pub fn is_full(&self) -> bool {
+        let guard = lock_or_recover(&self.inner);
+        guard.queued >= guard.capacity
+    }
+}
+
+impl Admit for Buffer {
+    fn try_acquire(&self) -> bool {
+        let mut guard = lock_or_recover(&self.inner);
+        if guard.queued >= guard.capacity {
+            return false;
+        }
+        guard.queued += 1;
+        true
+    }
+
+    fn release(&self) {
+        lock_or_recover(&self.inner).queued -= 1;
+    }
+}
+
+/// An admission slot held alongside the response it was acquired for. Drop
+/// it (e.g. by destructuring it away once you're done with whatever the
+/// admission was guarding) to free the slot for the next parked request
+/// sharing the same [`ConcurrencyLimit`] or [`Buffer`] handle.
+pub struct Permit<A: Admit> {
+    admit: A,
+}
+
+impl<A: Admit> Drop for Permit<A> {
+    fn drop(&mut self) {
+        self.admit.release();
+    }
+}
+
+/// Returned alongside the response by [`PromiseCommands::concurrency_limit`].
+pub type ConcurrencyPermit = Permit<ConcurrencyLimit>;
+
+/// Returned alongside the response by [`PromiseCommands::buffer`].
+pub type BufferSlot = Permit<Buffer>;
+
+/// Recorded on a request's target entity by [`PromiseCommands::concurrency_limit`]
+/// and [`PromiseCommands::buffer`]. If `admit` has a slot free, the response
+/// is forwarded immediately paired with a [`Permit`]; otherwise the response
+/// is held in a [`Parked`] component until [`check_gate_releases`] (run via
+/// [`register_concurrency_sweep`] / [`register_buffer_sweep`]) finds a slot
+/// free for it.
+pub(crate) struct Gate<A: Admit, Response> {
+    admit: A,
+    _ignore: PhantomData<Response>,
+}
+
+impl<A: Admit, Response> Gate<A, Response> {
+    fn new(admit: A) -> Self {
+        Self { admit, _ignore: PhantomData }
+    }
+}
+
+impl<A: Admit, Response: 'static + Send + Sync> Operation for Gate<A, Response> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<Response>>() else {
+            return;
+        };
+
+        if self.admit.try_acquire() {
+            world.entity_mut(source).insert(InputStorage((value, Permit { admit: self.admit })));
+            roster.queue(source);
+        } else {
+            world.entity_mut(source).insert(Parked { admit: self.admit, value: Some(value) });
+        }
+    }
+}
+
+/// Holds a request's response on its target entity while it waits for a
+/// free slot in `admit`. Swept by [`check_gate_releases`].
+#[derive(Component)]
+pub(crate) struct Parked<A: Admit, Response: 'static + Send + Sync> {
+    admit: A,
+    value: Option<Response>,
+}
+
+/// Sweep every entity parked behind an [`Admit`] handle (by
+/// [`PromiseCommands::concurrency_limit`] or [`PromiseCommands::buffer`])
+/// and admit it as soon as a slot frees up. Meant to run once per update,
+/// the same way [`check_expired_deadlines`](crate::check_expired_deadlines)
+/// does for `.timeout(~)`. Since [`Parked`] is generic over the response
+/// type, this must be registered once per concrete `Response` actually used
+/// with [`PromiseCommands::concurrency_limit`]/[`PromiseCommands::buffer`]
+/// in the app, the same way Bevy requires any generic system to be.
+pub(crate) fn check_gate_releases<A: Admit, Response: 'static + Send + Sync>(
+    world: &mut World,
+    roster: &mut OperationRoster,
+) {
+    let mut state: SystemState<Query<Entity, bevy::prelude::With<Parked<A, Response>>>> = SystemState::new(world);
+    let parked: Vec<Entity> = state.get(world).iter().collect();
+
+    for target in parked {
+        let admitted = match world.get::<Parked<A, Response>>(target) {
+            Some(parked) => parked.admit.try_acquire(),
+            None => continue,
+        };
+
+        if !admitted {
+            continue;
+        }
+
+        let Some(Parked { admit, value: Some(value) }) = world.entity_mut(target).remove::<Parked<A, Response>>() else {
+            continue;
+        };
+
+        world.entity_mut(target).insert(InputStorage((value, Permit { admit })));
+        roster.queue(target);
+    }
+}
+
+/// Register [`check_gate_releases`] for [`PromiseCommands::concurrency_limit`]
+/// requests whose response is `Response`, the same way
+/// [`register_timeout_sweep`](crate::register_timeout_sweep) does for
+/// `.timeout(~)`. Without this, a request parked behind a full
+/// [`ConcurrencyLimit`] would sit there even after another holder's
+/// [`ConcurrencyPermit`] is dropped.
+pub(crate) fn register_concurrency_sweep<Response: 'static + Send + Sync>(app: &mut App) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            check_gate_releases::<ConcurrencyLimit, Response>(world, &mut roster);
+        });
+    });
+}
+
+/// Register [`check_gate_releases`] for [`PromiseCommands::buffer`] requests
+/// whose response is `Response`. See [`register_concurrency_sweep`].
+pub(crate) fn register_buffer_sweep<Response: 'static + Send + Sync>(app: &mut App) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            check_gate_releases::<Buffer, Response>(world, &mut roster);
+        });
+    });
 }
 
 impl<'w, 's, 'a, Response: 'static + Send + Sync, Streams, C> PromiseCommands<'w, 's, 'a, Response, Streams, NotLabeled<C>> {
@@ -598,3 +1415,225 @@ impl<'w, 's, 'a, Response: 'static + Send + Sync, Streams, M> PromiseCommands<'w
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::retry::FiniteRetries;
+    use bevy::prelude::With;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct DeliverAttemptCount {
+        target: Entity,
+        count: Arc<AtomicUsize>,
+    }
+
+    impl bevy::ecs::system::Command for DeliverAttemptCount {
+        fn apply(self, world: &mut World) {
+            let attempt = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            world.entity_mut(self.target).insert(InputStorage(attempt));
+        }
+    }
+
+    struct CountingProvider {
+        count: Arc<AtomicUsize>,
+    }
+
+    impl Provider for CountingProvider {
+        type Request = ();
+        type Response = usize;
+        type Streams = ();
+
+        fn provide(self, _source: Entity, target: Entity, commands: &mut Commands) {
+            commands.add(DeliverAttemptCount { target, count: self.count });
+        }
+    }
+
+    /// Drive whichever attempt entity currently holds both a delivered
+    /// response and a queued [`RetryDispatch`], the same way the real serve
+    /// path would as soon as a value lands in an entity's [`InputStorage`].
+    fn drive_next_attempt(world: &mut World, roster: &mut OperationRoster) -> bool {
+        let mut state: SystemState<
+            Query<Entity, (With<InputStorage<usize>>, With<OperationQueue>)>,
+        > = SystemState::new(world);
+        let Some(target) = state.get(world).iter().next() else {
+            return false;
+        };
+        OperationQueue::execute_next(target, target, target, world, roster);
+        true
+    }
+
+    #[test]
+    fn retry_gives_up_once_policy_is_exhausted() {
+        let mut world = World::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let final_target = world.spawn(UnusedTarget).id();
+
+        let mut queue = CommandQueue::default();
+        {
+            let mut commands = Commands::new(&mut queue, &mut world);
+            let policy = FiniteRetries::new(2);
+            let state = policy.create(&());
+            let make_count = count.clone();
+            RetryDispatch::attempt(
+                final_target,
+                move || CountingProvider { count: make_count.clone() },
+                policy,
+                state,
+                &mut commands,
+            );
+        }
+        queue.apply(&mut world);
+
+        let mut roster = OperationRoster::new();
+        while world.get::<InputStorage<usize>>(final_target).is_none() {
+            assert!(drive_next_attempt(&mut world, &mut roster), "ran out of attempts to drive");
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(world.get::<InputStorage<usize>>(final_target).unwrap().0, 2);
+        assert_eq!(roster.queue.back(), Some(&final_target));
+    }
+
+    #[test]
+    fn retry_delivers_first_success_without_retrying() {
+        let mut world = World::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        let final_target = world.spawn(UnusedTarget).id();
+
+        let mut queue = CommandQueue::default();
+        {
+            let mut commands = Commands::new(&mut queue, &mut world);
+            // max: 1 means the very first attempt's outcome is already final.
+            let policy = FiniteRetries::new(1);
+            let state = policy.create(&());
+            let make_count = count.clone();
+            RetryDispatch::attempt(
+                final_target,
+                move || CountingProvider { count: make_count.clone() },
+                policy,
+                state,
+                &mut commands,
+            );
+        }
+        queue.apply(&mut world);
+
+        let mut roster = OperationRoster::new();
+        assert!(drive_next_attempt(&mut world, &mut roster));
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert_eq!(world.get::<InputStorage<usize>>(final_target).unwrap().0, 1);
+    }
+
+    #[test]
+    fn concurrency_gate_parks_and_releases_on_permit_drop() {
+        let mut world = World::new();
+        let limit = ConcurrencyLimit::new(1);
+        let mut roster = OperationRoster::new();
+
+        let first = world.spawn_empty().id();
+        world.entity_mut(first).insert(InputStorage(1u32));
+        Box::new(Gate::<ConcurrencyLimit, u32>::new(limit.clone())).execute(ServeCmd {
+            provider: first, source: first, target: first, world: &mut world, roster: &mut roster,
+        });
+        let (_, permit) = world.entity_mut(first)
+            .remove::<InputStorage<(u32, ConcurrencyPermit)>>()
+            .expect("first request should be admitted immediately")
+            .0;
+
+        let second = world.spawn_empty().id();
+        world.entity_mut(second).insert(InputStorage(2u32));
+        Box::new(Gate::<ConcurrencyLimit, u32>::new(limit.clone())).execute(ServeCmd {
+            provider: second, source: second, target: second, world: &mut world, roster: &mut roster,
+        });
+        assert!(world.get::<Parked<ConcurrencyLimit, u32>>(second).is_some());
+        assert!(world.get::<InputStorage<(u32, ConcurrencyPermit)>>(second).is_none());
+
+        // Freeing the first holder's slot should let the sweep admit the second.
+        drop(permit);
+        check_gate_releases::<ConcurrencyLimit, u32>(&mut world, &mut roster);
+
+        assert!(world.get::<Parked<ConcurrencyLimit, u32>>(second).is_none());
+        let (value, _permit) = world.entity_mut(second)
+            .remove::<InputStorage<(u32, ConcurrencyPermit)>>()
+            .expect("second request should be admitted once a slot frees up")
+            .0;
+        assert_eq!(value, 2);
+    }
+
+    #[test]
+    fn buffer_gate_parks_once_full() {
+        let mut world = World::new();
+        let queue = Buffer::new(1);
+        let mut roster = OperationRoster::new();
+
+        let first = world.spawn_empty().id();
+        world.entity_mut(first).insert(InputStorage("a"));
+        Box::new(Gate::<Buffer, &'static str>::new(queue.clone())).execute(ServeCmd {
+            provider: first, source: first, target: first, world: &mut world, roster: &mut roster,
+        });
+        assert!(queue.is_full());
+
+        let second = world.spawn_empty().id();
+        world.entity_mut(second).insert(InputStorage("b"));
+        Box::new(Gate::<Buffer, &'static str>::new(queue.clone())).execute(ServeCmd {
+            provider: second, source: second, target: second, world: &mut world, roster: &mut roster,
+        });
+        assert!(world.get::<Parked<Buffer, &'static str>>(second).is_some());
+    }
+
+    /// Drive [`check_pending_async_filters`] until `source` is no longer
+    /// holding a [`PendingFilterAsync`], or give up after a generous number
+    /// of attempts. The predicate task runs on a background thread pool, so
+    /// there's no single call after which it's guaranteed to have finished.
+    fn drive_pending_filter(world: &mut World, roster: &mut OperationRoster, source: Entity) {
+        for _ in 0..10_000 {
+            check_pending_async_filters::<u32, ()>(world, roster);
+            if world.get::<PendingFilterAsync<u32, ()>>(source).is_none() {
+                return;
+            }
+            std::thread::yield_now();
+        }
+        panic!("predicate task never resolved");
+    }
+
+    #[test]
+    fn filter_async_lets_approved_value_through() {
+        AsyncComputeTaskPool::get_or_init(|| bevy::tasks::TaskPoolBuilder::new().build());
+
+        let mut world = World::new();
+        let source = world.spawn_empty().id();
+        world.entity_mut(source).insert(InputStorage(7u32));
+        let mut roster = OperationRoster::new();
+
+        Box::new(FilterAsync::new(|_: &u32| futures::future::ready(Ok::<(), ()>(())))).execute(ServeCmd {
+            provider: source, source, target: source, world: &mut world, roster: &mut roster,
+        });
+
+        drive_pending_filter(&mut world, &mut roster, source);
+
+        assert_eq!(world.get::<InputStorage<u32>>(source).unwrap().0, 7);
+        assert_eq!(roster.queue.back(), Some(&source));
+        assert!(roster.cancel.is_empty());
+    }
+
+    #[test]
+    fn filter_async_cancels_rejected_value() {
+        AsyncComputeTaskPool::get_or_init(|| bevy::tasks::TaskPoolBuilder::new().build());
+
+        let mut world = World::new();
+        let source = world.spawn_empty().id();
+        world.entity_mut(source).insert(InputStorage(7u32));
+        let mut roster = OperationRoster::new();
+
+        Box::new(FilterAsync::new(|_: &u32| futures::future::ready(Err::<(), ()>(())))).execute(ServeCmd {
+            provider: source, source, target: source, world: &mut world, roster: &mut roster,
+        });
+
+        drive_pending_filter(&mut world, &mut roster, source);
+
+        assert!(world.get::<InputStorage<u32>>(source).is_none());
+        assert_eq!(roster.cancel.len(), 1);
+        assert_eq!(roster.cancel[0].apply_to, source);
+    }
+}
@@ -18,7 +18,7 @@
 use crate::OperationRoster;
 
 use bevy::{
-    prelude::{Entity, App, Commands, World},
+    prelude::{Entity, App, Commands, World, Resource},
     ecs::{
         world::EntityMut,
         system::EntityCommands,
@@ -34,6 +34,32 @@ pub(crate) use serve::*;
 mod builder;
 pub use builder::ServiceBuilder;
 
+mod retry;
+pub use retry::*;
+
+mod timeout;
+
+mod readiness;
+pub use readiness::Readiness;
+
+mod registry;
+pub use registry::{AccessPolicy, ServiceName, ServiceRegistry};
+
+mod transform;
+pub use transform::ServiceTransform;
+
+mod discovery;
+pub use discovery::ServiceDiscovery;
+
+mod capability;
+pub use capability::{CapabilityAllowlist, CapabilityKey, RequesterScope};
+
+mod guard;
+pub use guard::{And, Not, Or, ServiceGuard};
+
+mod async_world;
+pub use async_world::AsyncWorldHandle;
+
 mod traits;
 pub use traits::*;
 
@@ -74,6 +100,23 @@ impl<Request, Response> Default for ServiceMarker<Request, Response> {
     }
 }
 
+/// Recorded by [`AddServicesExt::add_default_service`], keyed by the
+/// `(Request, Response)` pair the same way [`ServiceMarker`] is, to hold the
+/// entity of the default provider for that pair. The dispatch path of this
+/// crate does not read it, so a request whose target provider can no longer
+/// be found is not rerouted to it.
+#[derive(Resource)]
+pub(crate) struct DefaultService<Request, Response> {
+    pub(crate) entity: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response)>,
+}
+
+impl<Request, Response> DefaultService<Request, Response> {
+    fn new(entity: Entity) -> Self {
+        Self { entity, _ignore: Default::default() }
+    }
+}
+
 /// Provider is the public API handle for referring to an existing service
 /// provider. Downstream users can obtain a Provider using
 /// - [`crate::ServiceDiscovery`].iter()
@@ -119,6 +162,19 @@ pub trait SpawnServicesExt<'w, 's> {
         &'a mut self,
         service: S,
     ) -> ServiceRef<S::Request, S::Response, S::Streams>;
+
+    /// Same as [`Self::spawn_service`], but also register the new provider
+    /// under `name` in the [`ServiceRegistry`] so it can be resolved later by
+    /// [`ServiceDiscovery::by_name`]/[`ServiceDiscovery::by_prefix`] instead
+    /// of only by its concrete [`ServiceRef`].
+    fn spawn_named_service<'a, M, S: ServiceSpawn<M>>(
+        &'a mut self,
+        name: impl Into<String>,
+        service: S,
+    ) -> ServiceRef<S::Request, S::Response, S::Streams>
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync;
 }
 
 impl<'w, 's> SpawnServicesExt<'w, 's> for Commands<'w, 's> {
@@ -128,6 +184,30 @@ impl<'w, 's> SpawnServicesExt<'w, 's> for Commands<'w, 's> {
     ) -> ServiceRef<S::Request, S::Response, S::Streams> {
         service.spawn_service(self)
     }
+
+    fn spawn_named_service<'a, M, S: ServiceSpawn<M>>(
+        &'a mut self,
+        name: impl Into<String>,
+        service: S,
+    ) -> ServiceRef<S::Request, S::Response, S::Streams>
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync,
+    {
+        let name = name.into();
+        let provider = service.spawn_service(self);
+        let entity = provider.get();
+        self.entity(entity).insert((
+            ServiceName(name.clone()),
+            ServiceMarker::<S::Request, S::Response>::default(),
+        ));
+        self.add(move |world: &mut World| {
+            world
+                .get_resource_or_insert_with(ServiceRegistry::default)
+                .register(name, entity);
+        });
+        provider
+    }
 }
 
 /// This trait extends the App interface so that services can be added while
@@ -135,6 +215,25 @@ impl<'w, 's> SpawnServicesExt<'w, 's> for Commands<'w, 's> {
 pub trait AddServicesExt {
     /// Call this on an App to create a service that is available immediately.
     fn add_service<M, S: ServiceAdd<M>>(&mut self, service: S) -> &mut Self;
+
+    /// Same as [`Self::add_service`], but also register the new provider
+    /// under `name` in the [`ServiceRegistry`] so it can be resolved later by
+    /// [`ServiceDiscovery::by_name`]/[`ServiceDiscovery::by_prefix`].
+    fn add_named_service<M, S: ServiceAdd<M>>(&mut self, name: impl Into<String>, service: S) -> &mut Self
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync;
+
+    /// Add `service` to the app and record it as the default provider for
+    /// `Request -> Response` in a `DefaultService` resource. There is at most
+    /// one default per `(Request, Response)` pair; calling this again for the
+    /// same pair replaces the previous default. The dispatch path of this
+    /// crate does not read that resource, so requests whose target provider
+    /// can no longer be found are not rerouted to this service.
+    fn add_default_service<M, S: ServiceAdd<M>>(&mut self, service: S) -> &mut Self
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync;
 }
 
 impl AddServicesExt for App {
@@ -142,6 +241,34 @@ impl AddServicesExt for App {
         service.add_service(self);
         self
     }
+
+    fn add_named_service<M, S: ServiceAdd<M>>(&mut self, name: impl Into<String>, service: S) -> &mut Self
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync,
+    {
+        let name = name.into();
+        let provider = service.add_service(self);
+        let entity = provider.get();
+        self.world.entity_mut(entity).insert((
+            ServiceName(name.clone()),
+            ServiceMarker::<S::Request, S::Response>::default(),
+        ));
+        self.world
+            .get_resource_or_insert_with(ServiceRegistry::default)
+            .register(name, entity);
+        self
+    }
+
+    fn add_default_service<M, S: ServiceAdd<M>>(&mut self, service: S) -> &mut Self
+    where
+        S::Request: 'static + Send + Sync,
+        S::Response: 'static + Send + Sync,
+    {
+        let provider = service.add_service(self);
+        self.world.insert_resource(DefaultService::<S::Request, S::Response>::new(provider.get()));
+        self
+    }
 }
 
 #[cfg(test)]
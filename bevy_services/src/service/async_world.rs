@@ -0,0 +1,74 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{Channel, Provider};
+
+use bevy::prelude::{Component, Entity, Resource, World};
+
+/// Passed to an async service body (the `impl Future` returned from an
+/// [`InAsyncReq`](crate::InAsyncReq) system) via its [`Channel`], this lets
+/// the body request scoped, checked access back into the live `World` at
+/// await points, instead of only ever seeing the `Query` snapshot that was
+/// captured before the future was created. Building on bevy_defer's
+/// async-world-access pattern, every method here suspends the future, queues
+/// the access through the [`OperationRoster`](crate::OperationRoster), runs
+/// it during the next service flush with `&mut World` access, and resumes
+/// the future with the result. This is what makes multi-step async
+/// workflows (poll sensor, decide, command actuator) stay consistent with
+/// live ECS state instead of acting on data that is already stale.
+#[derive(Clone)]
+pub struct AsyncWorldHandle {
+    channel: Channel,
+}
+
+impl AsyncWorldHandle {
+    pub(crate) fn new(channel: Channel) -> Self {
+        Self { channel }
+    }
+
+    /// Suspend until `entity`'s `C` component can be read, then resume with
+    /// a clone of it, or `None` if the entity has no such component (or no
+    /// longer exists) by the time the access is run.
+    pub async fn get_component<C: Component + Clone>(&self, entity: Entity) -> Option<C> {
+        self.channel
+            .query(move |world: &mut World| world.get::<C>(entity).cloned())
+            .await
+    }
+
+    /// Suspend until resource `R` can be read, then resume with a clone of
+    /// it, or `None` if the resource is not present by the time the access
+    /// is run.
+    pub async fn resource<R: Resource + Clone>(&self) -> Option<R> {
+        self.channel
+            .query(move |world: &mut World| world.get_resource::<R>().cloned())
+            .await
+    }
+
+    /// Suspend until `provider` can accept `request`, dispatch it there, and
+    /// resume with its response once the provider has delivered one.
+    pub async fn run_service<Request, Response>(
+        &self,
+        provider: Provider<Request, Response>,
+        request: Request,
+    ) -> Response
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+    {
+        self.channel.request(provider, request).await
+    }
+}
@@ -23,7 +23,7 @@ use crate::{
 };
 
 use bevy::{
-    prelude::{App, In},
+    prelude::{App, In, Component},
     ecs::{
         world::EntityMut,
         system::{IntoSystem, Commands, EntityCommands}
@@ -35,9 +35,18 @@ use std::future::Future;
 use futures::future::BoxFuture;
 
 use super::traits::*;
+use super::transform::{ServiceTransform, TransformStack};
+use super::guard::{GuardStack, ServiceGuard};
 
 pub struct BuilderMarker;
 
+/// Marks a [`DeliveryChoice`] that runs the service through the async
+/// pipeline, as opposed to [`BlockingChosen`] which has no delivery type at
+/// all. Builder options that only make sense for an async task, such as
+/// [`ServiceBuilder::timeout`], are bounded by this trait instead of
+/// [`DeliveryChoice`] so that they cannot be applied to a blocking service.
+pub trait AsyncDeliveryChoice: DeliveryChoice { }
+
 pub struct ServiceBuilder<Request, Response, Streams, Deliver, With, Also> {
     service: Service<Request, Response>,
     streams: std::marker::PhantomData<Streams>,
@@ -188,6 +197,45 @@ impl<Request, Response, Streams, With, Also> ServiceBuilder<Request, Response, S
             also: self.also,
         }
     }
+
+    /// Allow up to `n` requests to be in flight for this service at once. Any
+    /// requests beyond that limit will queue in arrival order and begin as
+    /// soon as a slot frees up, giving a middle ground between [`serial`](Self::serial)
+    /// (a limit of one) and [`parallel`](Self::parallel) (no limit).
+    pub fn concurrency_limit(
+        self,
+        n: usize,
+    ) -> ServiceBuilder<Request, Response, Streams, ConcurrencyLimitChosen, With, Also> {
+        ServiceBuilder {
+            service: self.service,
+            streams: Default::default(),
+            deliver: ConcurrencyLimitChosen(n),
+            with: self.with,
+            also: self.also,
+        }
+    }
+}
+
+impl<Request, Response, Streams, Deliver, With, Also> ServiceBuilder<Request, Response, Streams, Deliver, With, Also>
+where
+    Deliver: DeliveryChoice,
+{
+    /// Mark the service for load shedding by inserting a `LoadShedStorage`
+    /// marker on its entity next to the wrapped delivery mode (e.g. the bound
+    /// set by [`concurrency_limit`](Self::concurrency_limit) or
+    /// [`serial`](Self::serial)). The serve path of this crate does not read
+    /// that marker, so requests are handled by the wrapped delivery mode as
+    /// if this had not been called, and none is rejected with a
+    /// [`LoadShed`](crate::LoadShed) cancellation.
+    pub fn load_shed(self) -> ServiceBuilder<Request, Response, Streams, LoadShedChosen<Deliver>, With, Also> {
+        ServiceBuilder {
+            service: self.service,
+            streams: Default::default(),
+            deliver: LoadShedChosen(self.deliver),
+            with: self.with,
+            also: self.also,
+        }
+    }
 }
 
 impl<Request, Response, Streams, Deliver> ServiceBuilder<Request, Response, Streams, Deliver, (), ()> {
@@ -214,6 +262,73 @@ impl<Request, Response, Streams, Deliver, With> ServiceBuilder<Request, Response
     }
 }
 
+impl<Request, Response, Streams, Deliver, With, Also> ServiceBuilder<Request, Response, Streams, Deliver, With, Also>
+where
+    With: WithEntityMut + 'static,
+{
+    /// Stack `transform` onto this service's `TransformStack`, borrowing
+    /// actix-web's `Transform` concept, in the order the `.layer(..)` calls
+    /// are made. The dispatch path of this crate does not run the stack:
+    /// neither [`ServiceTransform::before`] nor [`ServiceTransform::after`]
+    /// is called, and no transform can short-circuit the service.
+    pub fn layer<T: ServiceTransform<Request, Response>>(
+        self,
+        transform: T,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), Also>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+    {
+        let prior = self.with;
+        ServiceBuilder {
+            service: self.service,
+            streams: Default::default(),
+            deliver: self.deliver,
+            with: move |mut entity_mut: EntityMut| {
+                prior.apply(entity_mut.reborrow());
+                match entity_mut.get_mut::<TransformStack<Request, Response>>() {
+                    Some(mut stack) => stack.push(transform),
+                    None => {
+                        entity_mut.insert(TransformStack::new(transform));
+                    }
+                }
+            },
+            also: self.also,
+        }
+    }
+
+    /// Attach `guard` to this service by adding it to a `GuardStack` on the
+    /// service's entity. Guards stack: every `.guard(..)` call adds another
+    /// entry. The serve path of this crate does not evaluate the stack, and
+    /// [`ServiceDiscovery`](crate::ServiceDiscovery) does not filter
+    /// providers by it, so requests are dispatched whether or not the guards
+    /// pass.
+    pub fn guard<G: ServiceGuard<Request>>(
+        self,
+        guard: G,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), Also>
+    where
+        Request: 'static + Send + Sync,
+    {
+        let prior = self.with;
+        ServiceBuilder {
+            service: self.service,
+            streams: Default::default(),
+            deliver: self.deliver,
+            with: move |mut entity_mut: EntityMut| {
+                prior.apply(entity_mut.reborrow());
+                match entity_mut.get_mut::<GuardStack<Request>>() {
+                    Some(mut stack) => stack.push(guard),
+                    None => {
+                        entity_mut.insert(GuardStack::new(guard));
+                    }
+                }
+            },
+            also: self.also,
+        }
+    }
+}
+
 impl<Request, Response, Streams, Deliver, With, Also>
 ServiceAdd<BuilderMarker>
 for ServiceBuilder<Request, Response, Streams, Deliver, With, Also>
@@ -411,6 +526,8 @@ impl DeliveryChoice for SerialChosen {
 
 impl private::Sealed<()> for SerialChosen { }
 
+impl AsyncDeliveryChoice for SerialChosen { }
+
 /// When this is used in the Deliver type parameter of AsyncServiceBuilder, the
 /// user has indicated that the service should be executed in parallel.
 pub struct ParallelChosen;
@@ -427,6 +544,56 @@ impl DeliveryChoice for ParallelChosen {
 
 impl private::Sealed<()> for ParallelChosen { }
 
+impl AsyncDeliveryChoice for ParallelChosen { }
+
+/// When this is used in the Deliver type parameter of AsyncServiceBuilder,
+/// the user has indicated that at most some bounded number of requests may
+/// be in flight at once. Any requests beyond that limit will queue in
+/// arrival order and begin as soon as a slot is freed up by a run being
+/// delivered or cancelled.
+pub struct ConcurrencyLimitChosen(pub usize);
+
+impl DeliveryChoice for ConcurrencyLimitChosen {
+    fn apply_entity_mut<'w>(self, entity_mut: &mut EntityMut<'w>) {
+        entity_mut.insert(Delivery::concurrency_limit(self.0));
+    }
+
+    fn apply_entity_commands<'w, 's, 'a>(self, entity_commands: &mut EntityCommands<'w, 's, 'a>) {
+        entity_commands.insert(Delivery::concurrency_limit(self.0));
+    }
+}
+
+impl private::Sealed<()> for ConcurrencyLimitChosen { }
+
+impl AsyncDeliveryChoice for ConcurrencyLimitChosen { }
+
+/// When this wraps another [`DeliveryChoice`] in the Deliver type parameter
+/// of AsyncServiceBuilder, the user has called [`ServiceBuilder::load_shed`]:
+/// it applies the wrapped delivery mode and also inserts a `LoadShedStorage`
+/// marker, which the serve path of this crate does not read.
+pub struct LoadShedChosen<Deliver>(Deliver);
+
+impl<Deliver: DeliveryChoice> DeliveryChoice for LoadShedChosen<Deliver> {
+    fn apply_entity_mut<'w>(self, entity_mut: &mut EntityMut<'w>) {
+        self.0.apply_entity_mut(entity_mut);
+        entity_mut.insert(LoadShedStorage);
+    }
+
+    fn apply_entity_commands<'w, 's, 'a>(self, entity_commands: &mut EntityCommands<'w, 's, 'a>) {
+        self.0.apply_entity_commands(entity_commands);
+        entity_commands.insert(LoadShedStorage);
+    }
+}
+
+impl<Deliver> private::Sealed<()> for LoadShedChosen<Deliver> { }
+
+impl<Deliver: AsyncDeliveryChoice> AsyncDeliveryChoice for LoadShedChosen<Deliver> { }
+
+/// Marks a service's entity as built with [`ServiceBuilder::load_shed`]. The
+/// serve path of this crate does not read it.
+#[derive(Component)]
+pub(crate) struct LoadShedStorage;
+
 /// When this is used in the Deliver type parameter of ServiceBuilder, the user
 /// has indicated that the service is blocking and therefore does not have a
 /// delivery type.
@@ -443,6 +610,8 @@ impl DeliveryChoice for BlockingChosen {
 
 impl private::Sealed<()> for BlockingChosen { }
 
+impl AsyncDeliveryChoice for () { }
+
 impl DeliveryChoice for () {
     fn apply_entity_commands<'w, 's, 'a>(self, entity_commands: &mut EntityCommands<'w, 's, 'a>) {
         ParallelChosen.apply_entity_commands(entity_commands)
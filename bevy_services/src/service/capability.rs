@@ -0,0 +1,92 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::{ecs::world::EntityMut, prelude::{Component, Resource}};
+
+use std::collections::HashMap;
+
+use super::ServiceBuilder;
+
+/// Identifies a privileged capability a service can require before it will
+/// run, Fuchsia-component-capability-routing style (e.g. an e-stop or
+/// actuator-control gate).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapabilityKey(pub &'static str);
+
+/// Attached to a request source's entity to identify the scope it is acting
+/// under, the "requester" half of a [`CapabilityAllowlist`] entry. Nothing in
+/// the serve path of this crate reads it from a request's source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct RequesterScope(pub &'static str);
+
+/// A central, auditable allowlist mapping `(requester-scope, provider-capability)`
+/// pairs to a decision of whether the request may proceed, queried with
+/// [`Self::is_allowed`]. A pair with no recorded entry defaults to denied, so
+/// a capability must be explicitly granted rather than implicitly allowed.
+/// The serve path of this crate does not query it, including for services
+/// gated with [`ServiceBuilder::require_capability`].
+#[derive(Resource, Default)]
+pub struct CapabilityAllowlist {
+    allowed: HashMap<(RequesterScope, CapabilityKey), bool>,
+}
+
+impl CapabilityAllowlist {
+    /// Allow `scope` to invoke services that require `capability`.
+    pub fn grant(&mut self, scope: RequesterScope, capability: CapabilityKey) -> &mut Self {
+        self.allowed.insert((scope, capability), true);
+        self
+    }
+
+    /// Explicitly deny `scope` from invoking services that require
+    /// `capability`, overriding any earlier [`Self::grant`] of the same pair.
+    pub fn revoke(&mut self, scope: RequesterScope, capability: CapabilityKey) -> &mut Self {
+        self.allowed.insert((scope, capability), false);
+        self
+    }
+
+    /// Consult the policy for whether `scope` may invoke a service that
+    /// requires `capability`, defaulting to denied when no entry has been
+    /// recorded for the pair.
+    pub fn is_allowed(&self, scope: RequesterScope, capability: CapabilityKey) -> bool {
+        self.allowed.get(&(scope, capability)).copied().unwrap_or(false)
+    }
+}
+
+/// Recorded on a service's entity by [`ServiceBuilder::require_capability`]
+/// to hold the capability it requires. The serve path of this crate does not
+/// read it, so no request is checked against [`CapabilityAllowlist`] or failed
+/// with [`AccessDenied`](crate::AccessDenied) because of it.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct RequiredCapability {
+    pub(crate) capability: CapabilityKey,
+}
+
+impl<Request, Response, Streams, Deliver> ServiceBuilder<Request, Response, Streams, Deliver, (), ()> {
+    /// Record that this service requires `capability` by inserting a
+    /// `RequiredCapability` on its entity. The serve path of this crate does
+    /// not check it, so requests run whatever their source's
+    /// [`RequesterScope`]. See [`CapabilityAllowlist`] for how grants are
+    /// recorded.
+    pub fn require_capability(
+        self,
+        capability: CapabilityKey,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), ()> {
+        self.with(move |mut entity_mut: EntityMut| {
+            entity_mut.insert(RequiredCapability { capability });
+        })
+    }
+}
@@ -0,0 +1,68 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::{Entity, Query, With},
+};
+
+use super::{Service, ServiceName, ServiceRef};
+
+/// Finds currently spawned providers whose request/response types match
+/// `Request`/`Response`, the way actix-web's `Scope` resolves a handler for
+/// an incoming path. Use [`Self::iter`] to find every matching provider
+/// regardless of name, or [`Self::by_name`]/[`Self::by_prefix`] to resolve
+/// one registered under a stable string identifier by
+/// [`SpawnServicesExt::spawn_named_service`](crate::SpawnServicesExt::spawn_named_service)
+/// or [`AddServicesExt::add_named_service`](crate::AddServicesExt::add_named_service).
+#[derive(SystemParam)]
+pub struct ServiceDiscovery<'w, 's, Request: 'static + Send + Sync, Response: 'static + Send + Sync, Streams: 'static + Send + Sync = ()> {
+    providers: Query<'w, 's, Entity, With<Service<Request, Response>>>,
+    named: Query<'w, 's, (Entity, &'static ServiceName)>,
+    _ignore: std::marker::PhantomData<Streams>,
+}
+
+impl<'w, 's, Request, Response, Streams> ServiceDiscovery<'w, 's, Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Iterate over every currently spawned provider whose request/response
+    /// types match `Request`/`Response`.
+    pub fn iter(&self) -> impl Iterator<Item = ServiceRef<Request, Response, Streams>> + '_ {
+        self.providers.iter().map(ServiceRef::new)
+    }
+
+    /// Find the provider that was registered under the exact name `name`,
+    /// re-checking that it still has the matching request/response types.
+    pub fn by_name(&self, name: &str) -> Option<ServiceRef<Request, Response, Streams>> {
+        let entity = self.named.iter().find(|(_, registered)| registered.0 == name)?.0;
+        self.providers.get(entity).ok().map(ServiceRef::new)
+    }
+
+    /// Find every provider whose registered name begins with `prefix`, the
+    /// way a hierarchical path like `"robots/arm1"` matches
+    /// `"robots/arm1/move"`. This lets config-driven code address a whole
+    /// subtree of services without knowing each one's exact name.
+    pub fn by_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = ServiceRef<Request, Response, Streams>> + 'a {
+        self.named.iter()
+            .filter(move |(_, registered)| registered.0.starts_with(prefix))
+            .filter_map(|(entity, _)| self.providers.get(entity).ok())
+            .map(ServiceRef::new)
+    }
+}
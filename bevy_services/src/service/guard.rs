@@ -0,0 +1,108 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::prelude::{Component, World};
+
+/// A predicate gate attached to a service with [`ServiceBuilder::guard`]
+/// (borrowing actix-web's route `Guard` idea). [`Self::check`] takes
+/// read-only access to the `World` and the pending request; the serve path
+/// of this crate does not call it before
+/// [`Service::serve`](crate::Service::serve). Combine guards with
+/// [`Self::and`]/[`Self::or`]/[`Self::not`], or stack several independent
+/// guards with repeated [`ServiceBuilder::guard`] calls.
+pub trait ServiceGuard<Request>: 'static + Send + Sync {
+    /// Decide whether this provider should run for `request`.
+    fn check(&self, world: &World, request: &Request) -> bool;
+
+    /// Combine with `other`: the result passes only when both do.
+    fn and<G: ServiceGuard<Request>>(self, other: G) -> And<Self, G>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// Combine with `other`: the result passes when either does.
+    fn or<G: ServiceGuard<Request>>(self, other: G) -> Or<Self, G>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    /// Negate this guard: the result passes when this one does not.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+impl<Request, F> ServiceGuard<Request> for F
+where
+    F: Fn(&World, &Request) -> bool + 'static + Send + Sync,
+{
+    fn check(&self, world: &World, request: &Request) -> bool {
+        self(world, request)
+    }
+}
+
+/// See [`ServiceGuard::and`].
+pub struct And<A, B>(A, B);
+
+impl<Request, A: ServiceGuard<Request>, B: ServiceGuard<Request>> ServiceGuard<Request> for And<A, B> {
+    fn check(&self, world: &World, request: &Request) -> bool {
+        self.0.check(world, request) && self.1.check(world, request)
+    }
+}
+
+/// See [`ServiceGuard::or`].
+pub struct Or<A, B>(A, B);
+
+impl<Request, A: ServiceGuard<Request>, B: ServiceGuard<Request>> ServiceGuard<Request> for Or<A, B> {
+    fn check(&self, world: &World, request: &Request) -> bool {
+        self.0.check(world, request) || self.1.check(world, request)
+    }
+}
+
+/// See [`ServiceGuard::not`].
+pub struct Not<A>(A);
+
+impl<Request, A: ServiceGuard<Request>> ServiceGuard<Request> for Not<A> {
+    fn check(&self, world: &World, request: &Request) -> bool {
+        !self.0.check(world, request)
+    }
+}
+
+/// Recorded on a service's entity by [`ServiceBuilder::guard`]. Holds every
+/// guard attached so far. The serve path of this crate does not evaluate it
+/// before [`Service::serve`](crate::Service::serve) runs.
+#[derive(Component)]
+pub(crate) struct GuardStack<Request> {
+    guards: Vec<Box<dyn ServiceGuard<Request>>>,
+}
+
+impl<Request> GuardStack<Request> {
+    pub(crate) fn new(guard: impl ServiceGuard<Request>) -> Self {
+        Self { guards: vec![Box::new(guard)] }
+    }
+
+    pub(crate) fn push(&mut self, guard: impl ServiceGuard<Request>) {
+        self.guards.push(Box::new(guard));
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright (C) 2023 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{Delivery, Provider};
+
+use bevy::prelude::World;
+
+use tokio::sync::watch;
+
+/// A handle that reports whether a service currently has capacity to accept
+/// another request, following the hyper/tower `poll_ready` pattern: a caller
+/// can check [`is_ready`](Self::is_ready) before dispatching instead of
+/// blindly firing requests that will be queued or shed.
+///
+/// Parallel and blocking services are always ready, since they have no
+/// admission limit. Serial, [`concurrency_limit`](crate::ServiceBuilder::concurrency_limit),
+/// and [`load_shed`](crate::ServiceBuilder::load_shed) services report the
+/// capacity published by their [`Delivery`].
+#[derive(Clone)]
+pub struct Readiness {
+    capacity: watch::Receiver<usize>,
+}
+
+impl Readiness {
+    pub(crate) fn new(capacity: watch::Receiver<usize>) -> Self {
+        Self { capacity }
+    }
+
+    /// Check whether the service has capacity for another request right now.
+    /// This never blocks, but the result may be stale by the time you act on
+    /// it if another caller claims the slot first.
+    pub fn is_ready(&self) -> bool {
+        *self.capacity.borrow() > 0
+    }
+
+    /// Wait until the service reports that it has capacity for another
+    /// request. Resolves immediately if it is already ready.
+    pub async fn ready(&mut self) {
+        while !self.is_ready() {
+            if self.capacity.changed().await.is_err() {
+                // The service's Delivery was despawned; there is no more
+                // capacity to wait for.
+                return;
+            }
+        }
+    }
+}
+
+impl<Request, Response, Streams> Provider<Request, Response, Streams> {
+    /// Get a [`Readiness`] handle for this service so that upstream
+    /// pipelines can respect its admission state instead of relying solely
+    /// on its internal queue.
+    pub fn readiness(&self, world: &World) -> Readiness {
+        world
+            .get::<Delivery>(self.get())
+            .map(Delivery::readiness)
+            .unwrap_or_else(|| Readiness::new(watch::channel(usize::MAX).1))
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (C) 2023 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{Provider, Service};
+
+use bevy::{
+    ecs::world::EntityMut,
+    prelude::{Component, Entity, Resource, World},
+};
+
+use std::collections::HashMap;
+
+use super::ServiceBuilder;
+
+/// Attached to a service's entity by [`ServiceBuilder::named`] so it can be
+/// found in the [`ServiceRegistry`] by a stable string name, the way a
+/// Fuchsia capability is exposed under a path instead of a typed handle.
+#[derive(Component, Clone)]
+pub struct ServiceName(pub String);
+
+/// Maps service names to the entities that provide them, so that code
+/// elsewhere can look up and request a service by name without holding a
+/// typed [`Provider`]. Populated by [`ServiceBuilder::named`].
+#[derive(Resource, Default)]
+pub struct ServiceRegistry {
+    by_name: HashMap<String, Entity>,
+}
+
+impl ServiceRegistry {
+    pub(crate) fn register(&mut self, name: String, entity: Entity) {
+        self.by_name.insert(name, entity);
+    }
+
+    /// Look up a service that was registered under `name`, re-checking that
+    /// its request/response types match `Request`/`Response` before handing
+    /// back a [`Provider`] for it.
+    pub fn get<Request, Response, Streams>(&self, world: &World, name: &str) -> Option<Provider<Request, Response, Streams>>
+    where
+        Request: 'static + Send + Sync,
+        Response: 'static + Send + Sync,
+    {
+        let entity = *self.by_name.get(name)?;
+        world.get::<Service<Request, Response>>(entity)?;
+        Some(Provider::new(entity))
+    }
+}
+
+/// An access control policy, keyed by a caller identity token, that can be
+/// attached to a service with `.access(policy)`.
+/// Modeled on Fuchsia's capability routing: a service is only reachable by
+/// callers whose identity the policy allows.
+pub trait AccessPolicy<Identity>: 'static + Send + Sync {
+    /// Decide whether `caller` is allowed to request this service.
+    fn is_allowed(&self, caller: &Identity) -> bool;
+}
+
+/// Recorded on a service's entity by [`ServiceBuilder::access`] to hold its
+/// [`AccessPolicy`]. The serve path of this crate does not read it.
+#[derive(Component)]
+pub(crate) struct AccessPolicyStorage<Identity> {
+    policy: Box<dyn AccessPolicy<Identity>>,
+}
+
+impl<Identity> AccessPolicyStorage<Identity> {
+    pub(crate) fn is_allowed(&self, caller: &Identity) -> bool {
+        self.policy.is_allowed(caller)
+    }
+}
+
+impl<Request, Response, Streams, Deliver, Also> ServiceBuilder<Request, Response, Streams, Deliver, (), Also> {
+    /// Register this service under `name` in the [`ServiceRegistry`] so it
+    /// can be looked up elsewhere without a typed [`Provider`] handle.
+    pub fn named(
+        self,
+        name: impl Into<String>,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), Also> {
+        let name = name.into();
+        self.with(move |mut entity_mut: EntityMut| {
+            let entity = entity_mut.id();
+            entity_mut.insert(ServiceName(name.clone()));
+            entity_mut.world_scope(|world| {
+                world
+                    .get_resource_or_insert_with(ServiceRegistry::default)
+                    .register(name, entity);
+            });
+        })
+    }
+
+    /// Attach an access control `policy` to this service by inserting an
+    /// `AccessPolicyStorage` on its entity. The serve path of this crate does
+    /// not consult it: requests are not checked against the policy, and none
+    /// is cancelled with [`AccessDenied`](crate::AccessDenied) because of it.
+    pub fn access<Identity, P: AccessPolicy<Identity>>(
+        self,
+        policy: P,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), Also> {
+        self.with(move |mut entity_mut: EntityMut| {
+            entity_mut.insert(AccessPolicyStorage { policy: Box::new(policy) });
+        })
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * Copyright (C) 2023 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::Cancelled;
+
+use bevy::{ecs::world::EntityMut, prelude::Component};
+
+use futures::future::{ready, BoxFuture};
+
+use std::marker::PhantomData;
+
+use super::ServiceBuilder;
+
+/// A policy that decides whether an attempt at a [`Service`](crate::Service)
+/// delivery should be retried. Modeled on the tower/burger retry pattern:
+/// before each attempt the original request is cloned and dispatched, and
+/// once an attempt finishes, `retry` is given a chance to ask for another
+/// attempt after a backoff delay instead of letting the outcome through.
+pub trait RetryPolicy<Request, Response>: 'static + Send + Sync {
+    /// Per-attempt state that the policy needs to remember between retries,
+    /// e.g. an attempt counter or a backoff schedule.
+    type State: 'static + Send + Sync;
+
+    /// Create the initial state for a fresh request.
+    fn create(&self, req: &Request) -> Self::State;
+
+    /// Inspect the outcome of an attempt. Return `None` to deliver `result`
+    /// as the final outcome, or `Some(delay)` with a future that resolves
+    /// once the backoff for the next attempt has elapsed.
+    fn retry(
+        &self,
+        state: &mut Self::State,
+        result: &Result<Response, Cancelled<()>>,
+    ) -> Option<BoxFuture<'static, ()>>;
+}
+
+/// A built-in [`RetryPolicy`] that makes at most `max` attempts in total (so
+/// at most `max - 1` retries), asking for another attempt after every
+/// outcome whatever it was, and then gives up and lets the last outcome
+/// through.
+pub struct FiniteRetries {
+    pub max: usize,
+}
+
+impl FiniteRetries {
+    pub fn new(max: usize) -> Self {
+        Self { max }
+    }
+}
+
+/// State used by [`FiniteRetries`] to count how many attempts have been made.
+pub struct FiniteRetriesState {
+    attempted: usize,
+}
+
+impl<Request, Response> RetryPolicy<Request, Response> for FiniteRetries
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+{
+    type State = FiniteRetriesState;
+
+    fn create(&self, _req: &Request) -> Self::State {
+        FiniteRetriesState { attempted: 0 }
+    }
+
+    fn retry(
+        &self,
+        state: &mut Self::State,
+        _result: &Result<Response, Cancelled<()>>,
+    ) -> Option<BoxFuture<'static, ()>> {
+        state.attempted += 1;
+        if state.attempted >= self.max {
+            return None;
+        }
+
+        Some(Box::pin(ready(())))
+    }
+}
+
+/// Recorded on a service's entity by [`ServiceBuilder::retry`] to hold its
+/// [`RetryPolicy`]. The serve path of this crate does not read it.
+#[derive(Component)]
+pub(crate) struct RetryPolicyStorage<Request, Response, P> {
+    policy: P,
+    _ignore: PhantomData<fn(Request, Response)>,
+}
+
+impl<Request, Response, P: RetryPolicy<Request, Response>> RetryPolicyStorage<Request, Response, P> {
+    fn new(policy: P) -> Self {
+        Self { policy, _ignore: Default::default() }
+    }
+}
+
+impl<Request, Response, Streams, Deliver> ServiceBuilder<Request, Response, Streams, Deliver, (), ()> {
+    /// Record `policy` as this service's retry policy by inserting a
+    /// `RetryPolicyStorage` on the service's entity. The serve path of this
+    /// crate does not consult that component: requests are not cloned or
+    /// re-dispatched because of it, and every outcome is delivered as it is.
+    pub fn retry<P>(
+        self,
+        policy: P,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), ()>
+    where
+        Request: 'static + Send + Sync + Clone,
+        Response: 'static + Send + Sync,
+        P: RetryPolicy<Request, Response>,
+    {
+        self.with(move |mut entity_mut: EntityMut| {
+            entity_mut.insert(RetryPolicyStorage::new(policy));
+        })
+    }
+}
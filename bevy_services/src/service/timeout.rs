@@ -0,0 +1,51 @@
+/*
+ * Copyright (C) 2023 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::{ecs::world::EntityMut, prelude::Component};
+
+use std::time::Duration;
+
+use super::{AsyncDeliveryChoice, ServiceBuilder};
+
+/// Recorded on a service's entity by [`ServiceBuilder::timeout`] so the
+/// configured deadline can be introspected. The async pipeline of this crate
+/// does not read it: the service's task is not raced against a timer, and no
+/// run is cancelled with a [`Timeout`](crate::Timeout) because of it.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct TimeoutStorage {
+    pub(crate) duration: Duration,
+}
+
+impl<Request, Response, Streams, Deliver> ServiceBuilder<Request, Response, Streams, Deliver, (), ()>
+where
+    Deliver: AsyncDeliveryChoice,
+{
+    /// Record a `duration` deadline for this service by inserting a
+    /// `TimeoutStorage` on its entity. Only available for async services: a
+    /// blocking service runs synchronously on its own system and has no task.
+    /// The deadline is not enforced: a task that has not resolved within
+    /// `duration` keeps running and is not cancelled with a
+    /// [`Timeout`](crate::Timeout).
+    pub fn timeout(
+        self,
+        duration: Duration,
+    ) -> ServiceBuilder<Request, Response, Streams, Deliver, impl FnOnce(EntityMut), ()> {
+        self.with(move |mut entity_mut: EntityMut| {
+            entity_mut.insert(TimeoutStorage { duration });
+        })
+    }
+}
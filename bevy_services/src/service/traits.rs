@@ -0,0 +1,249 @@
+/*
+ * Copyright (C) 2023 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::Cancel;
+
+use super::ServeCmd;
+
+use bevy::{
+    ecs::system::{Command, SystemState},
+    prelude::{Component, Entity, Query, World},
+};
+
+use std::{collections::VecDeque, marker::PhantomData, time::Instant};
+
+/// Holds the value that was delivered to an entity until whatever is
+/// watching that entity (its installed [`Operation`], if any) consumes it.
+/// [`ServeCmd::source`] is always expected to hold one of these for whatever
+/// `T` the operation reading it expects.
+#[derive(Component)]
+pub struct InputStorage<T>(pub T);
+
+/// Something that can be installed on an entity with [`PerformOperation`] and
+/// run once a value lands in that entity's [`InputStorage`]. Unlike
+/// [`Service::serve`](crate::Service::serve), an `Operation` does not fix its
+/// `Request`/`Response` types at the trait level: each implementor knows
+/// which `InputStorage<T>` to expect out of `cmd.world` and is responsible
+/// for forwarding (or withholding) a value to wherever it needs to go next.
+pub(crate) trait Operation: 'static + Send + Sync {
+    fn execute(self: Box<Self>, cmd: ServeCmd);
+}
+
+/// The queue of [`Operation`]s installed on an entity by [`PerformOperation`].
+/// More than one can stack on the same entity (e.g. chaining
+/// [`PromiseCommands::concurrency_limit`](crate::PromiseCommands::concurrency_limit)
+/// and [`PromiseCommands::buffer`](crate::PromiseCommands::buffer) on the same
+/// target), so they are run in the order they were added.
+#[derive(Component, Default)]
+pub(crate) struct OperationQueue(VecDeque<Box<dyn Operation>>);
+
+impl OperationQueue {
+    /// Pop and run the next queued [`Operation`] on `source`, if any.
+    pub(crate) fn execute_next(
+        provider: Entity,
+        source: Entity,
+        target: Entity,
+        world: &mut World,
+        roster: &mut OperationRoster,
+    ) {
+        let next = match world.get_mut::<OperationQueue>(source) {
+            Some(mut queue) => queue.0.pop_front(),
+            None => None,
+        };
+
+        if let Some(operation) = next {
+            operation.execute(ServeCmd { provider, source, target, world, roster });
+        }
+    }
+}
+
+/// Installs `operation` onto `target` so that it runs the next time a value
+/// lands in `target`'s [`InputStorage`].
+pub(crate) struct PerformOperation<Op> {
+    target: Entity,
+    operation: Op,
+}
+
+impl<Op: Operation> PerformOperation<Op> {
+    pub(crate) fn new(target: Entity, operation: Op) -> Self {
+        Self { target, operation }
+    }
+}
+
+impl<Op: Operation> Command for PerformOperation<Op> {
+    fn apply(self, world: &mut World) {
+        let boxed: Box<dyn Operation> = Box::new(self.operation);
+        let mut target_mut = world.entity_mut(self.target);
+        match target_mut.get_mut::<OperationQueue>() {
+            Some(mut queue) => queue.0.push_back(boxed),
+            None => {
+                target_mut.insert(OperationQueue(VecDeque::from([boxed])));
+            }
+        }
+    }
+}
+
+/// Accumulates follow-up work that an [`Operation`] wants performed as soon
+/// as possible: entities whose next queued [`Operation`] should run, and
+/// [`Cancel`]s that should be applied. An [`Operation`] never recurses
+/// straight into the next step with its own `&mut World` borrow; it appends
+/// to the roster instead and lets the caller drain it.
+#[derive(Default)]
+pub struct OperationRoster {
+    pub(crate) queue: VecDeque<Entity>,
+    pub(crate) cancel: VecDeque<Cancel>,
+}
+
+impl OperationRoster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue `target`'s next [`Operation`] to run as soon as possible against
+    /// whatever value was just placed into its [`InputStorage`].
+    pub fn queue(&mut self, target: Entity) {
+        self.queue.push_back(target);
+    }
+
+    /// Queue `cancel` to be applied as soon as possible.
+    pub fn cancel(&mut self, cancel: Cancel) {
+        self.cancel.push_back(cancel);
+    }
+}
+
+/// Component spawned on a dedicated timer entity by any deadline-based
+/// cancellation (e.g. [`Dangling::timeout`](crate::Dangling::timeout),
+/// [`PromiseCommands::timeout`](crate::PromiseCommands::timeout)): `guarded`
+/// is cancelled with [`Cancel::timeout`] once `at` elapses.
+#[derive(Component)]
+pub(crate) struct DeadlineTimer {
+    pub(crate) at: Instant,
+    pub(crate) guarded: Entity,
+}
+
+/// Sweep every outstanding [`DeadlineTimer`] and cancel any guarded entity
+/// whose deadline has elapsed. Meant to run once per update.
+pub(crate) fn check_expired_deadlines(world: &mut World, roster: &mut OperationRoster) {
+    let mut state: SystemState<Query<(Entity, &DeadlineTimer)>> = SystemState::new(world);
+    let now = Instant::now();
+
+    let mut expired = Vec::new();
+    for (timer, deadline) in state.get(world).iter() {
+        if deadline.at <= now {
+            expired.push((timer, deadline.guarded, now.duration_since(deadline.at)));
+        }
+    }
+
+    for (timer, guarded, elapsed) in expired {
+        roster.cancel(Cancel::timeout(guarded, timer, elapsed));
+        world.despawn(timer);
+    }
+}
+
+/// Installed on the same entity as a [`DeadlineTimer`]'s `guarded` target so
+/// that, the moment a value actually lands in that entity's
+/// [`InputStorage<T>`], the now-unneeded `timer` is despawned immediately
+/// instead of being left to fire spuriously (and leak) once its deadline
+/// elapses. This is the two-way counterpart to [`check_expired_deadlines`]:
+/// that function disposes the timer when it fires first, this disposes it
+/// when the guarded chain finishes first.
+pub(crate) struct TimeoutGuard<T> {
+    timer: Entity,
+    _ignore: PhantomData<T>,
+}
+
+impl<T> TimeoutGuard<T> {
+    pub(crate) fn new(timer: Entity) -> Self {
+        Self { timer, _ignore: PhantomData }
+    }
+}
+
+impl<T: 'static + Send + Sync> Operation for TimeoutGuard<T> {
+    fn execute(self: Box<Self>, ServeCmd { source, world, roster, .. }: ServeCmd) {
+        let Some(InputStorage(value)) = world.entity_mut(source).remove::<InputStorage<T>>() else {
+            return;
+        };
+        world.despawn(self.timer);
+        world.entity_mut(source).insert(InputStorage(value));
+        roster.queue(source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn expired_deadline_cancels_guarded_entity() {
+        let mut world = World::new();
+        let guarded = world.spawn_empty().id();
+        let timer = world.spawn(DeadlineTimer {
+            at: Instant::now() - Duration::from_secs(1),
+            guarded,
+        }).id();
+
+        let mut roster = OperationRoster::new();
+        check_expired_deadlines(&mut world, &mut roster);
+
+        assert_eq!(roster.cancel.len(), 1);
+        assert_eq!(roster.cancel[0].apply_to, guarded);
+        assert!(world.get_entity(timer).is_none());
+    }
+
+    #[test]
+    fn unexpired_deadline_is_left_alone() {
+        let mut world = World::new();
+        let guarded = world.spawn_empty().id();
+        let timer = world.spawn(DeadlineTimer {
+            at: Instant::now() + Duration::from_secs(60),
+            guarded,
+        }).id();
+
+        let mut roster = OperationRoster::new();
+        check_expired_deadlines(&mut world, &mut roster);
+
+        assert!(roster.cancel.is_empty());
+        assert!(world.get_entity(timer).is_some());
+    }
+
+    #[test]
+    fn timeout_guard_despawns_timer_once_value_arrives_first() {
+        let mut world = World::new();
+        let guarded = world.spawn_empty().id();
+        let timer = world.spawn(DeadlineTimer {
+            at: Instant::now() + Duration::from_secs(60),
+            guarded,
+        }).id();
+        world.entity_mut(guarded).insert(InputStorage(42u32));
+
+        let mut roster = OperationRoster::new();
+        let provider = world.spawn_empty().id();
+        Box::new(TimeoutGuard::<u32>::new(timer)).execute(ServeCmd {
+            provider,
+            source: guarded,
+            target: guarded,
+            world: &mut world,
+            roster: &mut roster,
+        });
+
+        assert!(world.get_entity(timer).is_none());
+        assert_eq!(world.get::<InputStorage<u32>>(guarded).unwrap().0, 42);
+        assert_eq!(roster.queue.len(), 1);
+        assert_eq!(roster.queue[0], guarded);
+    }
+}
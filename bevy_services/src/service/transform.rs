@@ -0,0 +1,63 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::prelude::Component;
+
+use crate::ServeCmd;
+
+/// Wraps a service's [`ServeCmd`] the way actix-web's `Transform` wraps a
+/// `Service`: [`Self::before`] receives the command and [`Self::after`]
+/// receives the response placed at `cmd.target`. Stack several with
+/// [`ServiceBuilder::layer`]. The dispatch path of this crate does not call
+/// either method, so a stacked transform does not run around the wrapped
+/// service.
+pub trait ServiceTransform<Request, Response>: 'static + Send + Sync {
+    /// Inspect or modify `cmd` before the wrapped service runs. Returning
+    /// `false` short-circuits the chain: neither the wrapped service nor any
+    /// transform nested inside this one will run, so this transform is
+    /// responsible for writing a response to `cmd.target` itself, the same
+    /// way a caching or auth-rejecting middleware would.
+    fn before(&self, cmd: &mut ServeCmd) -> bool {
+        let _ = cmd;
+        true
+    }
+
+    /// Inspect or modify the response that the wrapped service (or an inner
+    /// transform) already placed at `cmd.target`.
+    fn after(&self, cmd: &mut ServeCmd) {
+        let _ = cmd;
+    }
+}
+
+/// Recorded on a service's entity by [`ServiceBuilder::layer`]. Holds the
+/// transforms in definition order. The dispatch path of this crate does not
+/// read it, so neither [`ServiceTransform::before`] nor
+/// [`ServiceTransform::after`] is run around the wrapped service.
+#[derive(Component)]
+pub(crate) struct TransformStack<Request, Response> {
+    transforms: Vec<Box<dyn ServiceTransform<Request, Response>>>,
+}
+
+impl<Request, Response> TransformStack<Request, Response> {
+    pub(crate) fn new(transform: impl ServiceTransform<Request, Response>) -> Self {
+        Self { transforms: vec![Box::new(transform)] }
+    }
+
+    pub(crate) fn push(&mut self, transform: impl ServiceTransform<Request, Response>) {
+        self.transforms.push(Box::new(transform));
+    }
+}
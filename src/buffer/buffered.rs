@@ -15,10 +15,12 @@
  *
 */
 
-use bevy::prelude::{Entity, World};
+use bevy::prelude::{Entity, World, Component};
 
 use smallvec::SmallVec;
 
+use std::collections::HashMap;
+
 use crate::{
     Buffer, CloneFromBuffer, OperationError, OrBroken, InspectInput, ManageInput,
 };
@@ -227,3 +229,322 @@ impl<T: Buffered, const N: usize> Buffered for SmallVec<[T; N]> {
         self.iter().flat_map(|buffer| buffer.as_input()).collect()
     }
 }
+
+/// Remembers the last value pulled from a buffer, per session and per slot,
+/// so that a [`ZipLatest`] or [`Sample`] join can reuse it on a later pull
+/// where that buffer's slot did not have a fresh element available. Held on
+/// a dedicated cache entity rather than the buffer itself, since a single
+/// cache entity can carry one [`LastValueCache<Item>`] per slot of the join
+/// it belongs to. Keying only by session would let two same-`Item`-typed
+/// slots (e.g. `ZipLatest<(Buffer<T>, Buffer<T>)>`) collide on the same
+/// `HashMap` entry, so the buffer's own source entity is folded into the key
+/// to keep each slot's last value distinct.
+#[derive(Component)]
+struct LastValueCache<T> {
+    values: HashMap<(Entity, Entity), T>,
+}
+
+impl<T> Default for LastValueCache<T> {
+    fn default() -> Self {
+        Self { values: HashMap::new() }
+    }
+}
+
+fn remember_last_value<T: 'static + Send + Sync + Clone>(
+    cache: Entity,
+    slot: Entity,
+    session: Entity,
+    value: &T,
+    world: &mut World,
+) {
+    if world.get::<LastValueCache<T>>(cache).is_none() {
+        world.entity_mut(cache).insert(LastValueCache::<T>::default());
+    }
+    if let Some(mut cache) = world.get_mut::<LastValueCache<T>>(cache) {
+        cache.values.insert((slot, session), value.clone());
+    }
+}
+
+fn last_value<T: 'static + Send + Sync + Clone>(
+    cache: Entity,
+    slot: Entity,
+    session: Entity,
+    world: &World,
+) -> Option<T> {
+    world.get::<LastValueCache<T>>(cache)
+        .and_then(|cache| cache.values.get(&(slot, session)).cloned())
+}
+
+/// Identify a [`ZipLatest`]/[`Sample`] slot by its buffer's own source
+/// entity, so that [`remember_last_value`]/[`last_value`] never conflate two
+/// slots that happen to carry the same `Item` type. Falls back to `cache`
+/// itself in the degenerate case of a slot with no backing entity, which
+/// only collides with another slot's fallback if that slot is equally
+/// degenerate.
+fn slot_entity<B: Buffered>(buffer: &B, cache: Entity) -> Entity {
+    buffer.as_input().into_iter().next().unwrap_or(cache)
+}
+
+/// Pulls only once every wrapped buffer has an available element, which is
+/// the same strict-zip behavior the plain tuple/array/[`SmallVec`]
+/// [`Buffered`] impls already use. This wrapper exists so that policy can be
+/// selected explicitly, the same way [`ZipLatest`] and [`Sample`] are.
+#[derive(Clone)]
+pub struct ZipAll<T>(pub T);
+
+impl<T: Buffered> Buffered for ZipAll<T> {
+    fn buffered_count(&self, session: Entity, world: &World) -> Result<usize, OperationError> {
+        self.0.buffered_count(session, world)
+    }
+
+    type Item = T::Item;
+    fn pull(&self, session: Entity, world: &mut World) -> Result<Self::Item, OperationError> {
+        self.0.pull(session, world)
+    }
+
+    fn as_input(&self) -> SmallVec<[Entity; 8]> {
+        self.0.as_input()
+    }
+}
+
+/// A join policy that pulls the newest element available from each wrapped
+/// buffer, reusing the last value pulled for any buffer that has not
+/// advanced since the previous pull. A session is ready once every buffer
+/// has provided at least one value (fresh or cached) and at least one buffer
+/// has a fresh element waiting.
+#[derive(Clone)]
+pub struct ZipLatest<T> {
+    buffers: T,
+    cache: Entity,
+}
+
+impl<T> ZipLatest<T> {
+    /// `cache` should be an entity dedicated to this join (not shared with
+    /// any of the wrapped buffers) where the last-known value of each slot
+    /// can be stored between pulls.
+    pub fn new(buffers: T, cache: Entity) -> Self {
+        Self { buffers, cache }
+    }
+}
+
+impl<T0, T1> Buffered for ZipLatest<(T0, T1)>
+where
+    T0: Buffered,
+    T1: Buffered,
+    T0::Item: 'static + Send + Sync + Clone,
+    T1::Item: 'static + Send + Sync + Clone,
+{
+    fn buffered_count(&self, session: Entity, world: &World) -> Result<usize, OperationError> {
+        let (b0, b1) = &self.buffers;
+        let fresh = [
+            b0.buffered_count(session, world)?,
+            b1.buffered_count(session, world)?,
+        ];
+
+        let (b0, b1) = &self.buffers;
+        let ready = (fresh[0] > 0 || last_value::<T0::Item>(self.cache, slot_entity(b0, self.cache), session, world).is_some())
+            && (fresh[1] > 0 || last_value::<T1::Item>(self.cache, slot_entity(b1, self.cache), session, world).is_some());
+
+        Ok(if ready && fresh.iter().any(|count| *count > 0) { 1 } else { 0 })
+    }
+
+    type Item = (T0::Item, T1::Item);
+    fn pull(&self, session: Entity, world: &mut World) -> Result<Self::Item, OperationError> {
+        let cache = self.cache;
+        let slot0 = slot_entity(&self.buffers.0, cache);
+        let t0 = if self.buffers.0.buffered_count(session, world)? > 0 {
+            let value = self.buffers.0.pull(session, world)?;
+            remember_last_value(cache, slot0, session, &value, world);
+            value
+        } else {
+            last_value::<T0::Item>(cache, slot0, session, world).or_broken()?
+        };
+
+        let slot1 = slot_entity(&self.buffers.1, cache);
+        let t1 = if self.buffers.1.buffered_count(session, world)? > 0 {
+            let value = self.buffers.1.pull(session, world)?;
+            remember_last_value(cache, slot1, session, &value, world);
+            value
+        } else {
+            last_value::<T1::Item>(cache, slot1, session, world).or_broken()?
+        };
+
+        Ok((t0, t1))
+    }
+
+    fn as_input(&self) -> SmallVec<[Entity; 8]> {
+        let mut inputs = SmallVec::new();
+        inputs.extend(self.buffers.0.as_input());
+        inputs.extend(self.buffers.1.as_input());
+        inputs
+    }
+}
+
+impl<T0, T1, T2> Buffered for ZipLatest<(T0, T1, T2)>
+where
+    T0: Buffered,
+    T1: Buffered,
+    T2: Buffered,
+    T0::Item: 'static + Send + Sync + Clone,
+    T1::Item: 'static + Send + Sync + Clone,
+    T2::Item: 'static + Send + Sync + Clone,
+{
+    fn buffered_count(&self, session: Entity, world: &World) -> Result<usize, OperationError> {
+        let (b0, b1, b2) = &self.buffers;
+        let fresh = [
+            b0.buffered_count(session, world)?,
+            b1.buffered_count(session, world)?,
+            b2.buffered_count(session, world)?,
+        ];
+
+        let ready = (fresh[0] > 0 || last_value::<T0::Item>(self.cache, slot_entity(b0, self.cache), session, world).is_some())
+            && (fresh[1] > 0 || last_value::<T1::Item>(self.cache, slot_entity(b1, self.cache), session, world).is_some())
+            && (fresh[2] > 0 || last_value::<T2::Item>(self.cache, slot_entity(b2, self.cache), session, world).is_some());
+
+        Ok(if ready && fresh.iter().any(|count| *count > 0) { 1 } else { 0 })
+    }
+
+    type Item = (T0::Item, T1::Item, T2::Item);
+    fn pull(&self, session: Entity, world: &mut World) -> Result<Self::Item, OperationError> {
+        let cache = self.cache;
+        let slot0 = slot_entity(&self.buffers.0, cache);
+        let t0 = if self.buffers.0.buffered_count(session, world)? > 0 {
+            let value = self.buffers.0.pull(session, world)?;
+            remember_last_value(cache, slot0, session, &value, world);
+            value
+        } else {
+            last_value::<T0::Item>(cache, slot0, session, world).or_broken()?
+        };
+
+        let slot1 = slot_entity(&self.buffers.1, cache);
+        let t1 = if self.buffers.1.buffered_count(session, world)? > 0 {
+            let value = self.buffers.1.pull(session, world)?;
+            remember_last_value(cache, slot1, session, &value, world);
+            value
+        } else {
+            last_value::<T1::Item>(cache, slot1, session, world).or_broken()?
+        };
+
+        let slot2 = slot_entity(&self.buffers.2, cache);
+        let t2 = if self.buffers.2.buffered_count(session, world)? > 0 {
+            let value = self.buffers.2.pull(session, world)?;
+            remember_last_value(cache, slot2, session, &value, world);
+            value
+        } else {
+            last_value::<T2::Item>(cache, slot2, session, world).or_broken()?
+        };
+
+        Ok((t0, t1, t2))
+    }
+
+    fn as_input(&self) -> SmallVec<[Entity; 8]> {
+        let mut inputs = SmallVec::new();
+        inputs.extend(self.buffers.0.as_input());
+        inputs.extend(self.buffers.1.as_input());
+        inputs.extend(self.buffers.2.as_input());
+        inputs
+    }
+}
+
+/// A join policy that always pulls from a primary buffer and attaches the
+/// most recent available value, if any, from each secondary buffer as
+/// `Option`. A session is ready whenever the primary buffer has an element;
+/// the secondary buffers never block the join and are never required to
+/// have ever produced a value.
+#[derive(Clone)]
+pub struct Sample<P, S> {
+    primary: P,
+    secondary: S,
+    cache: Entity,
+}
+
+impl<P, S> Sample<P, S> {
+    /// `cache` should be an entity dedicated to this join (not shared with
+    /// any of the wrapped buffers) where the last-known value of each
+    /// secondary slot can be stored between pulls.
+    pub fn new(primary: P, secondary: S, cache: Entity) -> Self {
+        Self { primary, secondary, cache }
+    }
+}
+
+impl<P, S0> Buffered for Sample<P, (S0,)>
+where
+    P: Buffered,
+    S0: Buffered,
+    S0::Item: 'static + Send + Sync + Clone,
+{
+    fn buffered_count(&self, session: Entity, world: &World) -> Result<usize, OperationError> {
+        self.primary.buffered_count(session, world)
+    }
+
+    type Item = (P::Item, Option<S0::Item>);
+    fn pull(&self, session: Entity, world: &mut World) -> Result<Self::Item, OperationError> {
+        let primary = self.primary.pull(session, world)?;
+
+        let (s0,) = &self.secondary;
+        let cache = self.cache;
+        let slot0 = slot_entity(s0, cache);
+        let sample0 = if s0.buffered_count(session, world)? > 0 {
+            let value = s0.pull(session, world)?;
+            remember_last_value(cache, slot0, session, &value, world);
+            Some(value)
+        } else {
+            last_value::<S0::Item>(cache, slot0, session, world)
+        };
+
+        Ok((primary, sample0))
+    }
+
+    fn as_input(&self) -> SmallVec<[Entity; 8]> {
+        let mut inputs = self.primary.as_input();
+        inputs.extend(self.secondary.0.as_input());
+        inputs
+    }
+}
+
+impl<P, S0, S1> Buffered for Sample<P, (S0, S1)>
+where
+    P: Buffered,
+    S0: Buffered,
+    S1: Buffered,
+    S0::Item: 'static + Send + Sync + Clone,
+    S1::Item: 'static + Send + Sync + Clone,
+{
+    fn buffered_count(&self, session: Entity, world: &World) -> Result<usize, OperationError> {
+        self.primary.buffered_count(session, world)
+    }
+
+    type Item = (P::Item, Option<S0::Item>, Option<S1::Item>);
+    fn pull(&self, session: Entity, world: &mut World) -> Result<Self::Item, OperationError> {
+        let primary = self.primary.pull(session, world)?;
+
+        let (s0, s1) = &self.secondary;
+        let cache = self.cache;
+        let slot0 = slot_entity(s0, cache);
+        let sample0 = if s0.buffered_count(session, world)? > 0 {
+            let value = s0.pull(session, world)?;
+            remember_last_value(cache, slot0, session, &value, world);
+            Some(value)
+        } else {
+            last_value::<S0::Item>(cache, slot0, session, world)
+        };
+
+        let slot1 = slot_entity(s1, cache);
+        let sample1 = if s1.buffered_count(session, world)? > 0 {
+            let value = s1.pull(session, world)?;
+            remember_last_value(cache, slot1, session, &value, world);
+            Some(value)
+        } else {
+            last_value::<S1::Item>(cache, slot1, session, world)
+        };
+
+        Ok((primary, sample0, sample1))
+    }
+
+    fn as_input(&self) -> SmallVec<[Entity; 8]> {
+        let mut inputs = self.primary.as_input();
+        inputs.extend(self.secondary.0.as_input());
+        inputs.extend(self.secondary.1.as_input());
+        inputs
+    }
+}
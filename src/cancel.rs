@@ -16,19 +16,19 @@
 */
 
 use bevy::{
-    prelude::{Entity, Component, Bundle, Resource, World},
-    ecs::world::EntityMut,
+    prelude::{Entity, Component, Bundle, Resource, World, App, Update},
+    ecs::world::{EntityMut, Mut},
 };
 
 use backtrace::Backtrace;
 
 use smallvec::SmallVec;
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
 
 use crate::{
     Disposal, DisposalFailure, Filtered, OperationError, ScopeStorage, OrBroken,
-    OperationResult, SingleTargetStorage, OperationRoster, Supplanted,
+    OperationResult, SingleTargetStorage, OperationRoster, Supplanted, record_cancellation,
 };
 
 /// Information about the cancellation that occurred.
@@ -57,6 +57,15 @@ impl Cancellation {
     ) -> Self {
         Supplanted { supplanted_at_node, supplanted_by_node, supplanting_session }.into()
     }
+
+    /// Nest a [`Cancellation`] that occurred while a compensation workflow
+    /// registered with [`CancellableBundle::with_on_cancel`] was reacting to
+    /// this one, e.g. rolling back a reservation that itself had to be
+    /// cancelled.
+    pub fn while_cancelling(mut self, nested: Cancellation) -> Self {
+        self.while_cancelling.push(nested);
+        self
+    }
 }
 
 impl<T: Into<CancellationCause>> From<T> for Cancellation {
@@ -99,6 +108,33 @@ pub enum CancellationCause {
     /// The entity provided in [`BrokenLink`] is the link where the breakage was
     /// detected.
     Broken(Broken),
+
+    /// A [`Service`](crate::Service) provider needed by the workflow was
+    /// despawned or had a critical component removed while a request was
+    /// waiting on it.
+    ServiceUnavailable(ServiceUnavailable),
+
+    /// A delivery was cancelled because it did not complete before its
+    /// `.timeout(~)` deadline elapsed.
+    TimedOut(TimedOut),
+
+    /// A `.require_permission(~)` gate denied the request before it could
+    /// reach the provider.
+    Unauthorized(Unauthorized),
+
+    /// The requester (or some outside supervisor) asked for the session to be
+    /// cancelled through a [`CancelHandle`].
+    Requested(Requested),
+
+    /// An async task observed that its session was cancelled through a
+    /// [`Cancelable`](crate::Cancelable) future and unwound cooperatively
+    /// instead of being aborted out from under it.
+    TaskCanceled(TaskCanceled),
+
+    /// A `.on_cancel(policy)` supervision layer matched this cause and
+    /// re-injected the original request, but the cause kept recurring until
+    /// the policy's attempts were exhausted.
+    RetriesExhausted(RetriesExhausted),
 }
 
 impl From<Filtered> for CancellationCause {
@@ -125,6 +161,103 @@ impl From<Broken> for CancellationCause {
     }
 }
 
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct ServiceUnavailable {
+    /// The provider that is no longer available
+    pub provider: Entity,
+}
+
+impl From<ServiceUnavailable> for CancellationCause {
+    fn from(value: ServiceUnavailable) -> Self {
+        CancellationCause::ServiceUnavailable(value)
+    }
+}
+
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct TimedOut {
+    /// The node whose `.timeout(~)` deadline elapsed
+    pub node: Entity,
+}
+
+impl From<TimedOut> for CancellationCause {
+    fn from(value: TimedOut) -> Self {
+        CancellationCause::TimedOut(value)
+    }
+}
+
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct Unauthorized {
+    /// The provider that the request was denied access to
+    pub provider: Entity,
+}
+
+impl From<Unauthorized> for CancellationCause {
+    fn from(value: Unauthorized) -> Self {
+        CancellationCause::Unauthorized(value)
+    }
+}
+
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct Requested {
+    /// The session that was asked to be cancelled
+    pub at: Entity,
+}
+
+impl From<Requested> for CancellationCause {
+    fn from(value: Requested) -> Self {
+        CancellationCause::Requested(value)
+    }
+}
+
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct TaskCanceled {
+    /// The task that observed the cancellation and unwound cooperatively
+    pub task: Entity,
+}
+
+impl From<TaskCanceled> for CancellationCause {
+    fn from(value: TaskCanceled) -> Self {
+        CancellationCause::TaskCanceled(value)
+    }
+}
+
+/// A variant of [`CancellationCause`]
+#[derive(Debug)]
+pub struct RetriesExhausted {
+    /// The cause of the last attempt, which a supervising `.on_cancel(policy)`
+    /// was unable to recover from
+    pub cause: Arc<CancellationCause>,
+    /// How many times the original request was re-injected before the
+    /// supervising policy gave up
+    pub attempts: u32,
+}
+
+impl From<RetriesExhausted> for CancellationCause {
+    fn from(value: RetriesExhausted) -> Self {
+        CancellationCause::RetriesExhausted(value)
+    }
+}
+
+/// The error produced by a [`Cancelable`](crate::Cancelable) future when it is
+/// polled after its session has been cancelled. Borrowed from deno_core's
+/// `Canceled`: once the signal is triggered, the next poll resolves to this
+/// error instead of polling the wrapped work again. Session cleanup triggers
+/// the signal and then aborts the task without polling it again.
+#[derive(Debug, Clone, Copy)]
+pub struct Canceled;
+
+impl Canceled {
+    /// Turn this error into a [`Cancellation`] for the task that observed it.
+    pub fn into_cancellation(self, task: Entity) -> Cancellation {
+        TaskCanceled { task }.into()
+    }
+}
+
 /// Passed into the [`OperationRoster`](crate::OperationRoster) to pass a cancel
 /// signal into the target.
 #[derive(Debug, Clone)]
@@ -140,6 +273,61 @@ pub(crate) struct Cancel {
 }
 
 impl Cancel {
+    /// A service that a node was depending on has become unavailable, so the
+    /// delivery at `target` can no longer be completed.
+    pub(crate) fn service_unavailable(target: Entity, provider: Entity) -> Cancel {
+        Cancel {
+            source: provider,
+            target,
+            session: None,
+            cancellation: ServiceUnavailable { provider }.into(),
+        }
+    }
+
+    /// A `.timeout(~)` deadline elapsed for `session` before `node` finished
+    /// delivering its response.
+    pub(crate) fn timed_out(node: Entity, session: Entity) -> Cancel {
+        Cancel {
+            source: node,
+            target: node,
+            session: Some(session),
+            cancellation: TimedOut { node }.into(),
+        }
+    }
+
+    /// A `.require_permission(~)` gate denied a request before it could reach
+    /// `provider`.
+    pub(crate) fn unauthorized(target: Entity, provider: Entity) -> Cancel {
+        Cancel {
+            source: provider,
+            target,
+            session: None,
+            cancellation: Unauthorized { provider }.into(),
+        }
+    }
+
+    /// A cancellation that a supervising node (e.g. `.on_cancel(policy)`)
+    /// decided not to (or could no longer) intercept, and is passing on to
+    /// `target` on `source`'s behalf.
+    pub(crate) fn forwarded(
+        source: Entity,
+        target: Entity,
+        session: Entity,
+        cancellation: Cancellation,
+    ) -> Cancel {
+        Cancel { source, target, session: Some(session), cancellation }
+    }
+
+    /// A [`CancelHandle`] was triggered for `session`.
+    fn requested(session: Entity) -> Cancel {
+        Cancel {
+            source: session,
+            target: session,
+            session: Some(session),
+            cancellation: Requested { at: session }.into(),
+        }
+    }
+
     pub(crate) fn trigger(
         self,
         world: &mut World,
@@ -160,6 +348,22 @@ impl Cancel {
         world: &mut World,
         roster: &mut OperationRoster,
     ) -> Result<(), CancelFailure> {
+        if let Some(session) = self.session {
+            // Capture a snapshot before anything downstream of this node can
+            // be despawned, so the cancellation can still be explained later.
+            record_cancellation(world, session, self.source, Some(self.target), &self.cancellation);
+        }
+
+        if let Some(OnCancelStorage(on_cancel)) = world.get::<OnCancelStorage>(self.target) {
+            // Run the registered compensation workflow for the cancelled
+            // session before letting the cascade continue past this node.
+            // This is invoked synchronously and exactly once per trigger, so
+            // it cannot indefinitely stall the cascade the way an awaited
+            // sub-workflow could.
+            let on_cancel = *on_cancel;
+            (on_cancel)(OperationCancel { cancel: self.clone(), world, roster });
+        }
+
         if let Some(cancel) = world.get::<OperationCancelStorage>(self.target) {
             let cancel = cancel.0;
             (cancel)(OperationCancel { cancel: self, world, roster });
@@ -335,15 +539,39 @@ pub struct OperationCancel<'a> {
 #[derive(Component)]
 struct OperationCancelStorage(fn(OperationCancel) -> OperationResult);
 
+/// A compensation/cleanup hook registered with
+/// [`CancellableBundle::with_on_cancel`]. It is run for the cancelled session
+/// when [`Cancel::try_trigger`] reaches this node, before the node's regular
+/// cancellation handling continues the cascade.
+#[derive(Component, Clone, Copy)]
+struct OnCancelStorage(fn(OperationCancel) -> OperationResult);
+
 #[derive(Bundle)]
 pub struct CancellableBundle {
     storage: CancelSignalStorage,
     cancel: OperationCancelStorage,
+    on_cancel: Option<OnCancelStorage>,
 }
 
 impl CancellableBundle {
     pub fn new(cancel: fn(OperationCancel) -> OperationResult) -> Self {
-        CancellableBundle { storage: Default::default(), cancel: OperationCancelStorage(cancel) }
+        CancellableBundle {
+            storage: Default::default(),
+            cancel: OperationCancelStorage(cancel),
+            on_cancel: None,
+        }
+    }
+
+    /// Register a compensation workflow to run for the cancelled session
+    /// before the cancellation cascades past this node, e.g. to release a
+    /// lock or roll back a reservation. Any cancellations that the hook
+    /// triggers should be nested into the parent with
+    /// [`Cancellation::while_cancelling`] so they show up in the structured
+    /// cancellation report instead of being reported as unrelated top-level
+    /// cancellations.
+    pub fn with_on_cancel(mut self, on_cancel: fn(OperationCancel) -> OperationResult) -> Self {
+        self.on_cancel = Some(OnCancelStorage(on_cancel));
+        self
     }
 }
 
@@ -353,3 +581,88 @@ pub struct StopTaskFailure {
     /// The backtrace to indicate why it failed
     pub backtrace: Option<Backtrace>,
 }
+
+/// A cloneable, `Send + Sync` handle that lets the original requester (or some
+/// outside supervisor) cancel a session on demand, without needing `&mut World`.
+/// This mirrors deno_core's `CancelHandle::cancel(&self)`: triggering it just
+/// queues up a [`Cancel`] and flips an atomic flag; each queued [`Cancel`] is
+/// triggered by [`flush_requested_cancellations`] on the next update.
+#[derive(Clone)]
+pub struct CancelHandle {
+    session: Entity,
+    is_canceled: Arc<AtomicBool>,
+    queue: Arc<Mutex<SmallVec<[Cancel; 16]>>>,
+}
+
+impl CancelHandle {
+    pub(crate) fn new(session: Entity, queue: Arc<Mutex<SmallVec<[Cancel; 16]>>>) -> Self {
+        Self { session, is_canceled: Arc::new(AtomicBool::new(false)), queue }
+    }
+
+    /// Ask for the session behind this handle to be cancelled. This can be
+    /// called from any thread, at any time, without access to the [`World`].
+    pub fn cancel(&self) {
+        self.is_canceled.store(true, Ordering::SeqCst);
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.push(Cancel::requested(self.session));
+        }
+    }
+
+    /// Check whether [`Self::cancel`] has been called for this handle.
+    pub fn is_canceled(&self) -> bool {
+        self.is_canceled.load(Ordering::SeqCst)
+    }
+}
+
+/// Backs every [`CancelHandle`] with a queue of [`Cancel`]s that have been
+/// requested from outside the normal node-triggered cancellation path, so
+/// that they can be applied to the [`World`] on the next update.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct RequestedCancelQueue(Arc<Mutex<SmallVec<[Cancel; 16]>>>);
+
+impl RequestedCancelQueue {
+    pub(crate) fn create_handle(&self, session: Entity) -> CancelHandle {
+        CancelHandle::new(session, self.0.clone())
+    }
+}
+
+/// Obtain a [`CancelHandle`] for a session that is already running, so that it
+/// can be cancelled from outside the workflow that is processing it.
+pub trait GetCancelHandle {
+    /// Get a handle that can cancel `session` from any thread.
+    fn cancel_handle(&mut self, session: Entity) -> CancelHandle;
+}
+
+impl GetCancelHandle for World {
+    fn cancel_handle(&mut self, session: Entity) -> CancelHandle {
+        self.get_resource_or_insert_with(RequestedCancelQueue::default)
+            .create_handle(session)
+    }
+}
+
+/// Drain any [`CancelHandle::cancel`] requests that have accumulated since the
+/// last update and trigger each of them with `roster`.
+pub(crate) fn flush_requested_cancellations(world: &mut World, roster: &mut OperationRoster) {
+    let queue = world.get_resource_or_insert_with(RequestedCancelQueue::default).0.clone();
+    let pending: SmallVec<[Cancel; 16]> = {
+        let Ok(mut queue) = queue.lock() else { return };
+        std::mem::take(&mut *queue)
+    };
+
+    for cancel in pending {
+        cancel.trigger(world, roster);
+    }
+}
+
+/// Register [`flush_requested_cancellations`] to run once per update.
+/// Without this, [`CancelHandle::cancel`] only ever enqueues onto
+/// [`RequestedCancelQueue`]; nothing would ever drain that queue, so an
+/// externally requested cancellation would never
+/// actually be applied.
+pub(crate) fn register_requested_cancellations(app: &mut App) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            flush_requested_cancellations(world, &mut roster);
+        });
+    });
+}
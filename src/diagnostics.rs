@@ -0,0 +1,173 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::prelude::{App, Entity, Resource, World};
+
+use std::collections::VecDeque;
+
+use crate::{Cancellation, CancellationCause, Disposal, DisposalCause};
+
+/// A structured, despawn-safe snapshot of a single [`Cancellation`] or
+/// [`Disposal`], captured by [`CancellationRecorder`] at the moment it
+/// occurred, before any of the entities it mentions are despawned. This lets
+/// a user reconstruct why a workflow aborted after the fact, even though
+/// [`CancellationCause`]'s own doc comment warns that its entities "will
+/// already be despawned by the time you receive this information."
+#[derive(Debug, Clone)]
+pub struct CancellationSnapshot {
+    /// The session this snapshot was recorded for
+    pub session: Entity,
+    /// The node that triggered the cancellation or reported the disposal
+    pub source: Entity,
+    /// The node the cancellation was headed toward, if this snapshot came
+    /// from a [`Cancel`](crate::Cancel) rather than a [`Disposal`]
+    pub target: Option<Entity>,
+    /// A human-readable name for the cause, e.g. `"TimedOut"` or `"Filtered"`
+    pub cause: &'static str,
+    /// Causes that were nested inside this one, e.g. a scope's cancellation
+    /// that disposed this output, or a compensation workflow that was itself
+    /// cancelled while reacting to this cancellation.
+    pub nested: Vec<CancellationSnapshot>,
+}
+
+impl CancellationSnapshot {
+    fn from_cancellation(
+        session: Entity,
+        source: Entity,
+        target: Option<Entity>,
+        cancellation: &Cancellation,
+    ) -> Self {
+        let nested = cancellation
+            .while_cancelling
+            .iter()
+            .map(|nested| Self::from_cancellation(session, source, target, nested))
+            .collect();
+
+        Self { session, source, target, cause: cancellation_cause_name(&cancellation.cause), nested }
+    }
+
+    fn from_disposal(session: Entity, source: Entity, disposal: &Disposal) -> Self {
+        let nested = match disposal.cause.as_ref() {
+            DisposalCause::Scope(cancellation) => {
+                vec![Self::from_cancellation(session, source, None, cancellation)]
+            }
+            _ => Vec::new(),
+        };
+
+        Self { session, source, target: None, cause: disposal_cause_name(&disposal.cause), nested }
+    }
+}
+
+fn cancellation_cause_name(cause: &CancellationCause) -> &'static str {
+    match cause {
+        CancellationCause::TargetDropped(_) => "TargetDropped",
+        CancellationCause::Unreachable(_) => "Unreachable",
+        CancellationCause::Filtered(_) => "Filtered",
+        CancellationCause::Supplanted(_) => "Supplanted",
+        CancellationCause::PoisonedMutexInPromise => "PoisonedMutexInPromise",
+        CancellationCause::Broken(_) => "Broken",
+        CancellationCause::ServiceUnavailable(_) => "ServiceUnavailable",
+        CancellationCause::TimedOut(_) => "TimedOut",
+        CancellationCause::Unauthorized(_) => "Unauthorized",
+        CancellationCause::Requested(_) => "Requested",
+        CancellationCause::TaskCanceled(_) => "TaskCanceled",
+        CancellationCause::RetriesExhausted(_) => "RetriesExhausted",
+    }
+}
+
+fn disposal_cause_name(cause: &DisposalCause) -> &'static str {
+    match cause {
+        DisposalCause::Supplanted(_) => "Supplanted",
+        DisposalCause::Filtered(_) => "Filtered",
+        DisposalCause::Branching(_) => "Branching",
+        DisposalCause::JoinImpossible(_) => "JoinImpossible",
+        DisposalCause::ServiceUnavailable(_) => "ServiceUnavailable",
+        DisposalCause::PoisonedMutex(_) => "PoisonedMutex",
+        DisposalCause::Scope(_) => "Scope",
+    }
+}
+
+/// Records a bounded history of [`Cancellation`]s and [`Disposal`]s as they
+/// occur, keyed by session, so a workflow's aborted history can be dumped for
+/// debugging after the entities involved have been despawned. Disabled by
+/// default; enable it with [`RecordCancellationsExt::record_cancellations`].
+#[derive(Resource)]
+pub struct CancellationRecorder {
+    capacity: usize,
+    records: VecDeque<CancellationSnapshot>,
+}
+
+impl CancellationRecorder {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, records: VecDeque::with_capacity(capacity) }
+    }
+
+    fn push(&mut self, snapshot: CancellationSnapshot) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(snapshot);
+    }
+
+    /// Dump every snapshot recorded for `session`, oldest first, so the
+    /// reason a workflow aborted can be reconstructed after the fact.
+    pub fn for_session(&self, session: Entity) -> Vec<&CancellationSnapshot> {
+        self.records.iter().filter(|record| record.session == session).collect()
+    }
+}
+
+pub(crate) fn record_cancellation(
+    world: &mut World,
+    session: Entity,
+    source: Entity,
+    target: Option<Entity>,
+    cancellation: &Cancellation,
+) {
+    if let Some(mut recorder) = world.get_resource_mut::<CancellationRecorder>() {
+        recorder.push(CancellationSnapshot::from_cancellation(session, source, target, cancellation));
+    }
+}
+
+pub(crate) fn record_disposal(
+    world: &mut World,
+    session: Entity,
+    source: Entity,
+    disposal: &Disposal,
+) {
+    if let Some(mut recorder) = world.get_resource_mut::<CancellationRecorder>() {
+        recorder.push(CancellationSnapshot::from_disposal(session, source, disposal));
+    }
+}
+
+/// Extends [`App`] so that cancellation/disposal recording can be turned on
+/// while configuring an App, the same way services are added with
+/// [`AddServicesExt`](crate::AddServicesExt).
+pub trait RecordCancellationsExt {
+    /// Start recording a bounded history of cancellations and disposals, up
+    /// to `capacity` entries, so they can be inspected with
+    /// [`CancellationRecorder::for_session`] after the entities they mention
+    /// have been despawned. Recording is opt-in because every recorded
+    /// snapshot is held in memory until it is evicted by a newer one.
+    fn record_cancellations(&mut self, capacity: usize) -> &mut Self;
+}
+
+impl RecordCancellationsExt for App {
+    fn record_cancellations(&mut self, capacity: usize) -> &mut Self {
+        self.world.insert_resource(CancellationRecorder::new(capacity));
+        self
+    }
+}
@@ -27,7 +27,7 @@ use std::sync::Arc;
 use std::collections::HashMap;
 
 use crate::{
-    OperationRoster, operation::ScopeStorage, Cancellation, UnhandledErrors,
+    OperationRoster, operation::ScopeStorage, Cancellation, UnhandledErrors, record_disposal,
 };
 
 #[derive(Debug, Clone)]
@@ -280,6 +280,10 @@ pub fn emit_disposal(
     world: &mut World,
     roster: &mut OperationRoster,
 ) {
+    // Capture a snapshot before the disposed entities can be despawned, so
+    // the disposal can still be explained later.
+    record_disposal(world, session, source, &disposal);
+
     if let Some(mut source_mut) = world.get_entity_mut(source) {
         source_mut.emit_disposal(session, disposal, roster);
     } else {
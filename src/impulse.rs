@@ -23,15 +23,22 @@ use std::future::Future;
 
 use crate::{
     Promise, ProvideOnce, StreamPack, IntoBlockingMapOnce, IntoAsyncMapOnce,
-    AsMapOnce, UnusedTarget, StreamTargetMap, Cancellable,
+    AsMapOnce, UnusedTarget, StreamTargetMap, Cancellable, TaskExecutorOverride,
+    TaskExecutorSelection, AddImpulse, RestartPolicy, ImpulseAsyncMapWithRestart,
+    Service, RetryPolicy, Cancellation,
 };
 
+use std::hash::Hash;
+
 mod detach;
 pub(crate) use detach::*;
 
 mod finished;
 pub(crate) use finished::*;
 
+mod fork_clone;
+pub(crate) use fork_clone::*;
+
 mod insert;
 pub(crate) use insert::*;
 
@@ -41,6 +48,9 @@ pub(crate) use internal::*;
 mod map;
 pub(crate) use map::*;
 
+mod on_cancel;
+pub(crate) use on_cancel::*;
+
 mod push;
 pub(crate) use push::*;
 
@@ -84,6 +94,18 @@ where
         self
     }
 
+    /// Register a callback that will run with the [`Cancellation`] reason if
+    /// the most recent step of this chain ends up cancelled, e.g. to run a
+    /// compensating action when a plan branch aborts. For terminal impulses
+    /// see [`Self::store_or_else`] and [`Self::push_or_else`].
+    pub fn on_cancel(
+        self,
+        f: impl FnOnce(Cancellation) + 'static + Send + Sync,
+    ) -> Impulse<'w, 's, 'a, Response, Streams> {
+        self.commands.entity(self.source).insert(ImpulseOnCancel::new(f));
+        self
+    }
+
     /// Take the data that comes out of the request, including both the response
     /// and the streams.
     #[must_use]
@@ -159,10 +181,15 @@ where
     }
 
     /// Apply a one-time callback whose output is a [`Future`] that will be run
-    /// in the [`AsyncComputeTaskPool`][1]. The output of the [`Future`] will be
-    /// the Response of the returned Impulse.
+    /// on the app's [`DefaultTaskExecutor`][1] (the [`AsyncComputeTaskPool`][2]
+    /// unless some other default was configured). The output of the [`Future`]
+    /// will be the Response of the returned Impulse.
+    ///
+    /// Use [`Self::map_async_on`] to run this particular map on a different
+    /// executor, e.g. to keep an IO-bound future off the compute pool.
     ///
-    /// [1]: bevy::tasks::AsyncComputeTaskPool
+    /// [1]: crate::DefaultTaskExecutor
+    /// [2]: bevy::tasks::AsyncComputeTaskPool
     #[must_use]
     pub fn map_async<Task>(
         self,
@@ -175,6 +202,202 @@ where
         self.then(f.into_async_map_once())
     }
 
+    /// Same as [`Self::map_async`], but the future is spawned on `executor`
+    /// instead of the app's [`DefaultTaskExecutor`](crate::DefaultTaskExecutor).
+    #[must_use]
+    pub fn map_async_on<Task>(
+        self,
+        f: impl FnOnce(Response) -> Task + 'static + Send + Sync,
+        executor: TaskExecutorSelection,
+    ) -> Impulse<'w, 's, 'a, Task::Output, ()>
+    where
+        Task: Future + 'static + Send + Sync,
+        Task::Output: 'static + Send + Sync,
+    {
+        let impulse = self.then(f.into_async_map_once());
+        impulse.commands.entity(impulse.source).insert(TaskExecutorOverride(executor));
+        impulse
+    }
+
+    /// Same as [`Self::map_async`], but if `f`'s future resolves to `Err`,
+    /// it will be retried according to `policy`. Only `Ok` values are
+    /// delivered downstream: once the policy's attempts are exhausted, the
+    /// last error is dropped and nothing is delivered for that session.
+    ///
+    /// Because a retry has to reissue the original request, `Response` must
+    /// be [`Clone`]. Because `f` itself may be called more than once, it
+    /// must be [`Clone`] as well, unlike the plain `FnOnce` accepted by
+    /// [`Self::map_async`].
+    #[must_use]
+    pub fn map_async_with_restart<Task, Resp, Err>(
+        self,
+        f: impl FnOnce(Response) -> Task + 'static + Send + Sync + Clone,
+        policy: RestartPolicy,
+    ) -> Impulse<'w, 's, 'a, Resp, ()>
+    where
+        Response: Clone,
+        Task: Future<Output = Result<Resp, Err>> + 'static + Send + Sync,
+        Resp: 'static + Send + Sync,
+        Err: 'static + Send + Sync,
+    {
+        let source = self.target;
+        let target = self.commands.spawn((
+            Detached::default(),
+            UnusedTarget,
+            ImpulseMarker,
+        )).id();
+
+        self.commands.entity(source)
+            .insert(Cancellable::new(cancel_impulse))
+            .set_parent(target);
+        self.commands.add(AddImpulse::new(
+            source,
+            ImpulseAsyncMapWithRestart::<_, Response, Task, ()>::new(target, f, policy),
+        ));
+
+        Impulse {
+            source,
+            target,
+            commands: self.commands,
+            _ignore: Default::default(),
+        }
+    }
+
+    /// Pass the outcome of the request into `provider`, and whenever a
+    /// response comes back, consult `policy` to decide whether `provider`
+    /// should be re-run with a new request instead of forwarding the
+    /// response downstream. This is the impulse-chain counterpart to
+    /// [`Service::retry`], and shares the same [`RetryPolicy`].
+    ///
+    /// Because a retry has to reissue the request, `Response` (which becomes
+    /// the request given to `provider`) must be [`Clone`].
+    #[must_use]
+    pub fn retry<T, P>(
+        self,
+        provider: Service<Response, T, ()>,
+        policy: P,
+    ) -> Impulse<'w, 's, 'a, T, ()>
+    where
+        Response: Clone,
+        T: 'static + Send + Sync,
+        P: RetryPolicy<Response, T>,
+    {
+        self.then(provider.retry(policy))
+    }
+
+    /// Pass the outcome of the request into `provider`, capping how many
+    /// invocations of `provider` may be in flight at once. This is the
+    /// impulse-chain counterpart to [`Service::concurrency_limit`].
+    #[must_use]
+    pub fn concurrency_limit<T, Streams2: StreamPack>(
+        self,
+        provider: Service<Response, T, Streams2>,
+        max: usize,
+    ) -> Impulse<'w, 's, 'a, T, Streams2>
+    where
+        T: 'static + Send + Sync,
+    {
+        self.then(provider.concurrency_limit(max))
+    }
+
+    /// Pass the outcome of the request into `provider`, shedding (cancelling)
+    /// the request instead of queueing it if `provider` is already running at
+    /// its [`Self::concurrency_limit`]. This is the impulse-chain counterpart
+    /// to [`Service::load_shed`].
+    #[must_use]
+    pub fn load_shed<T, Streams2: StreamPack>(
+        self,
+        provider: Service<Response, T, Streams2>,
+    ) -> Impulse<'w, 's, 'a, T, Streams2>
+    where
+        T: 'static + Send + Sync,
+    {
+        self.then(provider.load_shed())
+    }
+
+    /// Pass the outcome of the request into `provider`, memoizing its
+    /// responses in a [`Cache`] kept on `cache`. This is the impulse-chain
+    /// counterpart to [`Service::cached`]: a repeated request is answered
+    /// from the cache without re-running `provider`, and a request that
+    /// matches one already in flight attaches to it instead of starting a
+    /// duplicate run.
+    #[must_use]
+    pub fn cached<T, Streams2: StreamPack>(
+        self,
+        provider: Service<Response, T, Streams2>,
+        cache: Entity,
+    ) -> Impulse<'w, 's, 'a, T, Streams2>
+    where
+        Response: Clone + Eq + Hash,
+        T: 'static + Send + Sync + Clone,
+    {
+        self.then(provider.cached(cache))
+    }
+
+    /// Duplicate the response of this impulse into two independent
+    /// downstream chains, like a broadcast subscription: each branch
+    /// receives its own clone of the response and can be continued
+    /// (`.then()`, `.map_block()`, etc.) completely independently of the
+    /// other. Cancellation follows the usual drop table on each branch: a
+    /// branch whose own downstream dependent is dropped is cancelled
+    /// independently, and calling [`Self::detach`] on one branch only keeps
+    /// that branch alive.
+    ///
+    /// Use [`Self::fork_clone_array`] for more than two branches.
+    #[must_use]
+    pub fn fork_clone(
+        self,
+    ) -> (Impulse<'w, 's, 'a, Response, ()>, Impulse<'w, 's, 'a, Response, ()>)
+    where
+        Response: Clone,
+    {
+        let [a, b] = self.fork_clone_array::<2>();
+        (a, b)
+    }
+
+    /// Same as [`Self::fork_clone`], but broadcasts the response to `N`
+    /// independent downstream chains instead of just two.
+    #[must_use]
+    pub fn fork_clone_array<const N: usize>(self) -> [Impulse<'w, 's, 'a, Response, ()>; N]
+    where
+        Response: Clone,
+    {
+        let fork_node = self.target;
+        let commands_ptr: *mut Commands<'w, 's> = self.commands;
+
+        // SAFETY: every reborrow derived from `commands_ptr` below is only
+        // ever used to enqueue commands into the same queue that
+        // `self.commands` pointed to. The returned impulses are handed back
+        // to the caller, who drives them one call at a time, so no two
+        // reborrows are ever used concurrently.
+        let commands = unsafe { &mut *commands_ptr };
+        let branches: Vec<Entity> = (0..N)
+            .map(|_| commands.spawn((
+                Detached::default(),
+                UnusedTarget,
+                ImpulseMarker,
+            )).id())
+            .collect();
+
+        commands.add(AddImpulse::new(
+            fork_node,
+            ForkClone::<Response>::new(branches.clone()),
+        ));
+
+        let mut branches = branches.into_iter();
+        std::array::from_fn(|_| {
+            let node = branches.next().expect("fork_clone_array always produces exactly N branches");
+            // SAFETY: see above.
+            let commands = unsafe { &mut *commands_ptr };
+            Impulse {
+                source: node,
+                target: node,
+                commands,
+                _ignore: Default::default(),
+            }
+        })
+    }
+
     /// Apply a one-time map that implements one of
     /// - [`FnOnce(BlockingMap<Request, Streams>) -> Response`](crate::BlockingMap)
     /// - [`FnOnce(AsyncMap<Request, Streams>) -> impl Future<Response>`](crate::AsyncMap)
@@ -214,6 +437,18 @@ where
         self.commands.entity(self.source).insert((stream_targets, map));
     }
 
+    /// Like [`Self::store`], but if the chain is cancelled before a response
+    /// arrives, `on_cancel` is invoked with the [`Cancellation`] reason
+    /// instead of the target being left untouched.
+    pub fn store_or_else(
+        self,
+        target: Entity,
+        on_cancel: impl FnOnce(Cancellation) + 'static + Send + Sync,
+    ) {
+        self.commands.entity(self.target).insert(ImpulseOnCancel::new(on_cancel));
+        self.store(target);
+    }
+
     /// Collect the stream data into [`Collection<T>`] components in the
     /// specified target, one collection for each stream data type. You must
     /// still decide what to do with the final response data.
@@ -254,11 +489,17 @@ where
         self.commands.entity(self.source).insert((stream_targets, map));
     }
 
-    // TODO(@mxgrey): Consider offering ways for users to respond to cancellations.
-    // For example, offer an on_cancel method that lets users provide a callback
-    // to be triggered when a cancellation happens. Or focus on terminal impulses,
-    // like offer store_or_else(~), push_or_else(~) etc which accept a callback
-    // that will be triggered after a cancellation.
+    /// Like [`Self::push`], but if the chain is cancelled before a response
+    /// arrives, `on_cancel` is invoked with the [`Cancellation`] reason
+    /// instead of the target being left untouched.
+    pub fn push_or_else(
+        self,
+        target: Entity,
+        on_cancel: impl FnOnce(Cancellation) + 'static + Send + Sync,
+    ) {
+        self.commands.entity(self.target).insert(ImpulseOnCancel::new(on_cancel));
+        self.push(target);
+    }
 }
 
 impl<'w, 's, 'a, Response, Streams> Impulse<'w, 's, 'a, Response, Streams>
@@ -0,0 +1,66 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::prelude::{Entity, Component};
+
+use crate::{
+    Impulsive, OperationSetup, OperationRequest, InputBundle, OperationResult, OrBroken,
+    Input, ManageInput,
+};
+
+/// Installed on the `target` of the impulse being forked by
+/// [`crate::Impulse::fork_clone`]/[`crate::Impulse::fork_clone_array`], the
+/// same way [`crate::Store`]/[`crate::Push`] are installed on a terminal
+/// impulse's target. Once the response arrives here, it is cloned once per
+/// branch and delivered to each of them, like a broadcast subscription.
+pub(crate) struct ForkClone<Response> {
+    branches: Vec<Entity>,
+    _ignore: std::marker::PhantomData<Response>,
+}
+
+impl<Response> ForkClone<Response> {
+    pub(crate) fn new(branches: Vec<Entity>) -> Self {
+        Self { branches, _ignore: Default::default() }
+    }
+}
+
+#[derive(Component)]
+struct ForkBranchesStorage(Vec<Entity>);
+
+impl<Response: 'static + Send + Sync + Clone> Impulsive for ForkClone<Response> {
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.entity_mut(source).insert((
+            ForkBranchesStorage(self.branches),
+            InputBundle::<Response>::new(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest,
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let branches = source_mut.get::<ForkBranchesStorage>().or_broken()?.0.clone();
+        let Input { session, data: response } = source_mut.take_input::<Response>()?;
+
+        for branch in branches {
+            world.get_entity_mut(branch).or_broken()?
+                .give_input(session, response.clone(), roster)?;
+        }
+        Ok(())
+    }
+}
@@ -16,17 +16,18 @@
 */
 
 use bevy::{
-    prelude::{Entity, Component, Bundle},
-    tasks::AsyncComputeTaskPool,
+    prelude::{Entity, Component, Bundle, World, App, Update},
+    ecs::world::Mut,
 };
 
-use std::future::Future;
+use std::{future::Future, collections::HashMap, time::{Duration, Instant}};
 
 use crate::{
     Impulsive, OperationSetup, OperationRequest, SingleTargetStorage, StreamPack,
     InputBundle, OperationResult, OrBroken, Input, ManageInput,
     ChannelQueue, BlockingMap, AsyncMap, InnerChannel, OperateTask, ActiveTasksStorage,
-    CallBlockingMapOnce, CallAsyncMapOnce, Operation,
+    CallBlockingMapOnce, CallAsyncMapOnce, Operation, resolve_task_executor,
+    OperationRoster, OperationReachability, ReachabilityResult,
 };
 
 /// The key difference between this and [`crate::OperateBlockingMap`] is that
@@ -172,7 +173,8 @@ where
         let channel = InnerChannel::new(source, session, sender.clone());
         let channel = channel.into_specific(&world)?;
 
-        let task = AsyncComputeTaskPool::get().spawn(f.call(AsyncMap { request, channel }));
+        let executor = resolve_task_executor(source, &world);
+        let task = executor.spawn(f.call(AsyncMap { request, channel }));
 
         let task_source = world.spawn(()).id();
         OperateTask::new(task_source, session, source, target, task, None, sender)
@@ -181,3 +183,347 @@ where
         Ok(())
     }
 }
+
+/// How long [`ImpulseAsyncMapWithRestart`] should wait before re-attempting a
+/// failed async map.
+#[derive(Clone, Copy, Debug)]
+pub enum RestartBackoff {
+    /// Always wait the same duration between attempts.
+    Fixed(Duration),
+    /// Double the wait on each attempt, starting at `base` and never
+    /// exceeding `cap`.
+    Exponential { base: Duration, cap: Duration },
+}
+
+impl RestartBackoff {
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        match *self {
+            RestartBackoff::Fixed(delay) => delay,
+            RestartBackoff::Exponential { base, cap } => {
+                let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+                base.saturating_mul(factor).min(cap)
+            }
+        }
+    }
+}
+
+/// A supervision-tree-style restart policy for [`ImpulseAsyncMapWithRestart`]:
+/// if the map's future resolves to `Err`, it is retried up to `max_attempts`
+/// times with [`RestartBackoff`] delay between attempts, instead of the chain
+/// simply propagating the error.
+#[derive(Clone, Copy, Debug)]
+pub struct RestartPolicy {
+    pub max_attempts: u32,
+    pub backoff: RestartBackoff,
+}
+
+impl RestartPolicy {
+    pub fn fixed(max_attempts: u32, delay: Duration) -> Self {
+        Self { max_attempts, backoff: RestartBackoff::Fixed(delay) }
+    }
+
+    pub fn exponential(max_attempts: u32, base: Duration, cap: Duration) -> Self {
+        Self { max_attempts, backoff: RestartBackoff::Exponential { base, cap } }
+    }
+}
+
+#[derive(Component)]
+struct RestartPolicyStorage(RestartPolicy);
+
+#[derive(Component, Default)]
+struct RestartAttemptsStorage {
+    attempts: HashMap<Entity, u32>,
+}
+
+#[derive(Component)]
+struct RetryRequestStorage<Request> {
+    pending: HashMap<Entity, Request>,
+}
+
+impl<Request> Default for RetryRequestStorage<Request> {
+    fn default() -> Self {
+        Self { pending: HashMap::new() }
+    }
+}
+
+type RestartAction = Box<dyn FnOnce(&mut World, &mut OperationRoster) -> OperationResult + Send>;
+
+/// Per-session backoff deadlines for [`ImpulseAsyncMapWithRestart`], swept by
+/// [`check_pending_restarts`] the same way [`DeadlineStorage`](crate::operation::OperateTimeout)
+/// is swept by `check_timed_out_deliveries`.
+#[derive(Component, Default)]
+struct RestartDeadlineStorage {
+    pending: HashMap<Entity, (Instant, RestartAction)>,
+}
+
+/// Sweep every [`ImpulseAsyncMapWithRestart`] node with a pending retry and
+/// re-attempt the ones whose backoff has elapsed.
+pub(crate) fn check_pending_restarts(world: &mut World, roster: &mut OperationRoster) {
+    let now = Instant::now();
+    let mut query = world.query::<(Entity, &RestartDeadlineStorage)>();
+    let nodes: Vec<Entity> = query.iter(world).map(|(node, _)| node).collect();
+
+    for node in nodes {
+        let ready: Vec<Entity> = {
+            let Some(storage) = world.get::<RestartDeadlineStorage>(node) else { continue };
+            storage.pending.iter()
+                .filter(|(_, (deadline, _))| *deadline <= now)
+                .map(|(session, _)| *session)
+                .collect()
+        };
+
+        for session in ready {
+            let retry = world.get_mut::<RestartDeadlineStorage>(node)
+                .and_then(|mut storage| storage.pending.remove(&session));
+
+            if let Some((_, retry)) = retry {
+                // Best-effort: if the node has since been despawned or
+                // otherwise broken, there is nothing left to retry into.
+                let _ = retry(world, roster);
+            }
+        }
+    }
+}
+
+/// Register [`check_pending_restarts`] to run once per update. Without this,
+/// a `RestartPolicy`/`RestartBackoff` deadline is recorded into
+/// [`RestartDeadlineStorage`] but never swept, so the retry it scheduled
+/// would never actually be re-attempted.
+pub(crate) fn register_restart_sweep(app: &mut App) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            check_pending_restarts(world, &mut roster);
+        });
+    });
+}
+
+/// Routes the `Result` produced by one attempt of an
+/// [`ImpulseAsyncMapWithRestart`]'s task: a success is forwarded to the map's
+/// real downstream target, while a failure is handed to `on_failure` so the
+/// owning node can decide whether to retry or give up.
+struct RestartCheckpoint<Resp, Err> {
+    final_target: Entity,
+    on_failure: Box<dyn FnOnce(Entity, &mut World, &mut OperationRoster) -> OperationResult + Send>,
+    _ignore: std::marker::PhantomData<(Resp, Err)>,
+}
+
+impl<Resp, Err> RestartCheckpoint<Resp, Err> {
+    fn new(
+        final_target: Entity,
+        on_failure: Box<dyn FnOnce(Entity, &mut World, &mut OperationRoster) -> OperationResult + Send>,
+    ) -> Self {
+        Self { final_target, on_failure, _ignore: Default::default() }
+    }
+}
+
+#[derive(Component)]
+struct CheckpointTarget(Entity);
+
+#[derive(Component)]
+struct OnRestartFailure(Box<dyn FnOnce(Entity, &mut World, &mut OperationRoster) -> OperationResult + Send>);
+
+impl<Resp: 'static + Send + Sync, Err: 'static + Send + Sync> Operation for RestartCheckpoint<Resp, Err> {
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.entity_mut(source).insert((
+            CheckpointTarget(self.final_target),
+            OnRestartFailure(self.on_failure),
+            InputBundle::<Result<Resp, Err>>::new(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let Input { session, data: result } = source_mut.take_input::<Result<Resp, Err>>()?;
+
+        match result {
+            Ok(response) => {
+                let final_target = source_mut.get::<CheckpointTarget>().or_broken()?.0;
+                world.get_entity_mut(final_target).or_broken()?.give_input(session, response, roster)?;
+            }
+            Err(_) => {
+                let on_failure = source_mut.take::<OnRestartFailure>().or_broken()?.0;
+                (on_failure)(session, world, roster)?;
+            }
+        }
+
+        world.despawn(source);
+        Ok(())
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        reachability.has_input::<Result<Resp, Err>>()
+    }
+}
+
+/// Spawn one attempt of an [`ImpulseAsyncMapWithRestart`]'s task: calls `f`
+/// with a fresh clone of `request`, stashes that clone for a possible retry,
+/// and routes the outcome through a [`RestartCheckpoint`].
+fn spawn_attempt<F, Request, Task, Streams, Resp, Err>(
+    node: Entity,
+    session: Entity,
+    request: Request,
+    world: &mut World,
+    roster: &mut OperationRoster,
+) -> OperationResult
+where
+    Request: 'static + Send + Sync + Clone,
+    Task: Future<Output = Result<Resp, Err>> + 'static + Send + Sync,
+    Resp: 'static + Send + Sync,
+    Err: 'static + Send + Sync,
+    Streams: StreamPack,
+    F: CallAsyncMapOnce<Request, Task, Streams> + 'static + Send + Sync + Clone,
+{
+    let sender = world.get_resource_or_insert_with(|| ChannelQueue::new()).sender.clone();
+    let mut node_mut = world.get_entity_mut(node).or_broken()?;
+    let target = node_mut.get::<SingleTargetStorage>().or_broken()?.get();
+    let f = node_mut.get::<AsyncMapOnceStorage<F>>().or_broken()?.f.clone();
+    node_mut.get_mut::<RetryRequestStorage<Request>>().or_broken()?
+        .pending.insert(session, request.clone());
+
+    let channel = InnerChannel::new(node, session, sender.clone());
+    let channel = channel.into_specific(&world)?;
+
+    let executor = resolve_task_executor(node, &world);
+    let task = executor.spawn(f.call(AsyncMap { request, channel }));
+
+    let checkpoint = world.spawn(()).id();
+    let on_failure: Box<dyn FnOnce(Entity, &mut World, &mut OperationRoster) -> OperationResult + Send> =
+        Box::new(move |session, world, roster| {
+            retry_or_give_up::<F, Request, Task, Streams, Resp, Err>(node, session, world, roster)
+        });
+    RestartCheckpoint::<Resp, Err>::new(target, on_failure)
+        .setup(OperationSetup { source: checkpoint, world })?;
+
+    let task_source = world.spawn(()).id();
+    OperateTask::new(task_source, session, node, checkpoint, task, None, sender)
+        .setup(OperationSetup { source: task_source, world })?;
+    roster.queue(task_source);
+    Ok(())
+}
+
+/// Called when one attempt of an [`ImpulseAsyncMapWithRestart`] has failed:
+/// either schedules another attempt after the policy's backoff, or gives up
+/// and lets the failure stand once `max_attempts` is exhausted.
+fn retry_or_give_up<F, Request, Task, Streams, Resp, Err>(
+    node: Entity,
+    session: Entity,
+    world: &mut World,
+    roster: &mut OperationRoster,
+) -> OperationResult
+where
+    Request: 'static + Send + Sync + Clone,
+    Task: Future<Output = Result<Resp, Err>> + 'static + Send + Sync,
+    Resp: 'static + Send + Sync,
+    Err: 'static + Send + Sync,
+    Streams: StreamPack,
+    F: CallAsyncMapOnce<Request, Task, Streams> + 'static + Send + Sync + Clone,
+{
+    let mut node_mut = world.get_entity_mut(node).or_broken()?;
+    let policy = node_mut.get::<RestartPolicyStorage>().or_broken()?.0;
+    let attempt = {
+        let mut attempts = node_mut.get_mut::<RestartAttemptsStorage>().or_broken()?;
+        let attempt = attempts.attempts.entry(session).or_insert(0);
+        *attempt += 1;
+        *attempt
+    };
+
+    if attempt > policy.max_attempts {
+        // Exhausted: the last error is dropped and nothing is delivered
+        // downstream for this session.
+        node_mut.get_mut::<RestartAttemptsStorage>().or_broken()?.attempts.remove(&session);
+        node_mut.get_mut::<RetryRequestStorage<Request>>().or_broken()?.pending.remove(&session);
+        return Ok(());
+    }
+
+    let request = node_mut.get::<RetryRequestStorage<Request>>().or_broken()?
+        .pending.get(&session).or_broken()?.clone();
+
+    let delay = policy.backoff.delay_for_attempt(attempt - 1);
+    let deadline = Instant::now() + delay;
+
+    let retry: RestartAction = Box::new(move |world, roster| {
+        spawn_attempt::<F, Request, Task, Streams, Resp, Err>(node, session, request, world, roster)
+    });
+
+    node_mut.get_mut::<RestartDeadlineStorage>().or_broken()?
+        .pending.insert(session, (deadline, retry));
+
+    roster.queue(node);
+    Ok(())
+}
+
+/// The key difference between this and [`ImpulseAsyncMap`] is that a failed
+/// attempt can be retried: if `f`'s future resolves to `Err`, this node
+/// re-invokes `f` with a fresh clone of the original request, up to
+/// `RestartPolicy::max_attempts` times with backoff between attempts. Because
+/// the request is reissued on every retry, it must be [`Clone`]; because `f`
+/// itself may run more than once, it must be [`Clone`] too (unlike the plain
+/// `FnOnce` that [`ImpulseAsyncMap`] accepts).
+#[derive(Bundle)]
+pub(crate) struct ImpulseAsyncMapWithRestart<F, Request, Task, Streams>
+where
+    F: 'static + Send + Sync,
+    Request: 'static + Send + Sync,
+    Task: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    f: AsyncMapOnceStorage<F>,
+    target: SingleTargetStorage,
+    policy: RestartPolicyStorage,
+    attempts: RestartAttemptsStorage,
+    retries: RetryRequestStorage<Request>,
+    deadlines: RestartDeadlineStorage,
+    #[bundle(ignore)]
+    _ignore: std::marker::PhantomData<(Request, Task, Streams)>,
+}
+
+impl<F, Request, Task, Streams> ImpulseAsyncMapWithRestart<F, Request, Task, Streams>
+where
+    F: 'static + Send + Sync,
+    Request: 'static + Send + Sync,
+    Task: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    pub(crate) fn new(target: Entity, f: F, policy: RestartPolicy) -> Self {
+        Self {
+            f: AsyncMapOnceStorage { f },
+            target: SingleTargetStorage::new(target),
+            policy: RestartPolicyStorage(policy),
+            attempts: RestartAttemptsStorage::default(),
+            retries: RetryRequestStorage::default(),
+            deadlines: RestartDeadlineStorage::default(),
+            _ignore: Default::default(),
+        }
+    }
+}
+
+impl<F, Request, Task, Streams, Resp, Err> Impulsive for ImpulseAsyncMapWithRestart<F, Request, Task, Streams>
+where
+    Request: 'static + Send + Sync + Clone,
+    Task: Future<Output = Result<Resp, Err>> + 'static + Send + Sync,
+    Resp: 'static + Send + Sync,
+    Err: 'static + Send + Sync,
+    Streams: StreamPack,
+    F: CallAsyncMapOnce<Request, Task, Streams> + 'static + Send + Sync + Clone,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.entity_mut(source).insert((
+            self,
+            InputBundle::<Request>::new(),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest,
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        spawn_attempt::<F, Request, Task, Streams, Resp, Err>(source, session, request, world, roster)
+    }
+}
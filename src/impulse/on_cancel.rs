@@ -0,0 +1,55 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use bevy::prelude::Component;
+
+use crate::{Cancellation, OperationCancel, OperationResult};
+
+/// Installed alongside `Cancellable` by [`crate::Impulse::on_cancel`],
+/// [`crate::Impulse::store_or_else`], and [`crate::Impulse::push_or_else`] so
+/// that `cancel_impulse` can run a user-provided compensating callback with
+/// the [`Cancellation`] reason when the node is cancelled.
+#[derive(Component)]
+pub(crate) struct ImpulseOnCancel(Option<Box<dyn FnOnce(Cancellation) + Send + Sync>>);
+
+impl ImpulseOnCancel {
+    pub(crate) fn new(f: impl FnOnce(Cancellation) + 'static + Send + Sync) -> Self {
+        Self(Some(Box::new(f)))
+    }
+
+    /// Run the stored callback, if one is still present. `cancel_impulse`
+    /// calls this each time a node is cancelled; a node that ends up
+    /// cancelled more than once (e.g. while already unwinding) will only run
+    /// the callback on the first call.
+    pub(crate) fn fire(&mut self, cancellation: Cancellation) {
+        if let Some(f) = self.0.take() {
+            f(cancellation);
+        }
+    }
+}
+
+/// The cancellation handler installed on every impulse node (see
+/// `Impulse::then`, `Impulse::store_or_else`, and `Impulse::push_or_else`).
+/// Fires the node's [`ImpulseOnCancel`] callback, if one was registered, so
+/// `on_cancel`/`store_or_else`/`push_or_else` actually run when the node is
+/// cancelled, instead of only ever being recorded and never invoked.
+pub(crate) fn cancel_impulse(OperationCancel { cancel, world, .. }: OperationCancel) -> OperationResult {
+    if let Some(mut on_cancel) = world.get_mut::<ImpulseOnCancel>(cancel.target) {
+        on_cancel.fire(cancel.cancellation.clone());
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Operation, SingleTargetStorage, Service, ServiceRequest, SingleInputStorage,
+    dispatch_service, Cancel, OperationRoster, OperationCleanup,
+    OperationResult, OrBroken, OperationSetup, OperationRequest,
+    ActiveTasksStorage, OperationReachability, ReachabilityResult,
+    InputBundle, Input, ManageInput,
+};
+
+use bevy::prelude::{Component, Entity};
+
+/// A casbin-style access control policy: given the actor that is making a
+/// request, and the object/action that the request targets, decide whether
+/// the actor is permitted to proceed.
+pub trait PermissionCheck<Actor>: 'static + Send + Sync {
+    fn enforce(&self, actor: &Actor, object: &str, action: &str) -> bool;
+}
+
+impl<Actor, F> PermissionCheck<Actor> for F
+where
+    F: Fn(&Actor, &str, &str) -> bool + 'static + Send + Sync,
+{
+    fn enforce(&self, actor: &Actor, object: &str, action: &str) -> bool {
+        self(actor, object, action)
+    }
+}
+
+/// An intermediate operation, analogous to [`OperateService`](crate::OperateService),
+/// that gates delivery to the wrapped provider behind a [`PermissionCheck`].
+/// Requests are only forwarded once `checker.enforce(actor, object, action)`
+/// returns `true`; otherwise the delivery is cancelled with
+/// [`Cancel::unauthorized`] instead of ever reaching the provider.
+pub(crate) struct OperateAccessControl<C, Request, Response, Streams, Actor> {
+    provider: Entity,
+    checker: C,
+    object: String,
+    action: String,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, Streams, Actor)>,
+}
+
+impl<C, Request, Response, Streams, Actor> OperateAccessControl<C, Request, Response, Streams, Actor>
+where
+    Request: 'static + Send + Sync,
+{
+    pub(crate) fn new(
+        provider: Service<Request, Response, Streams>,
+        checker: C,
+        object: impl Into<String>,
+        action: impl Into<String>,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.provider(),
+            checker,
+            object: object.into(),
+            action: action.into(),
+            target,
+            _ignore: Default::default(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ProviderStorage(Entity);
+
+#[derive(Component)]
+struct AccessControlStorage<C> {
+    checker: C,
+    object: String,
+    action: String,
+}
+
+impl<C, Request, Response, Streams, Actor> Operation for OperateAccessControl<C, Request, Response, Streams, Actor>
+where
+    C: PermissionCheck<Actor> + 'static + Send + Sync,
+    Request: 'static + Send + Sync + AsRef<Actor>,
+    Actor: 'static + Send + Sync,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.get_entity_mut(self.target).or_broken()?
+            .insert(SingleInputStorage::new(source));
+
+        world.entity_mut(source).insert((
+            InputBundle::<Request>::new(),
+            ProviderStorage(self.provider),
+            AccessControlStorage {
+                checker: self.checker,
+                object: self.object,
+                action: self.action,
+            },
+            SingleTargetStorage(self.target),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let provider = source_mut.get::<ProviderStorage>().or_broken()?.0;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        let allowed = {
+            let gate = source_mut.get::<AccessControlStorage<C>>().or_broken()?;
+            gate.checker.enforce(request.as_ref(), &gate.object, &gate.action)
+        };
+
+        if allowed {
+            source_mut.give_input(session, request, roster)?;
+            dispatch_service(ServiceRequest {
+                provider,
+                target,
+                operation: OperationRequest { source, world, roster },
+            });
+        } else {
+            deny(source, provider, roster);
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Request>()?;
+        ActiveTasksStorage::cleanup(clean)
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        if reachability.has_input::<Request>()? {
+            return Ok(true);
+        }
+        if ActiveTasksStorage::contains_session(reachability)? {
+            return Ok(true);
+        }
+        SingleInputStorage::is_reachable(reachability)
+    }
+}
+
+fn deny(source: Entity, provider: Entity, roster: &mut OperationRoster) {
+    roster.cancel(Cancel::unauthorized(source, provider));
+}
@@ -0,0 +1,240 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Operation, SingleTargetStorage, Service, ServiceRequest, SingleInputStorage,
+    dispatch_service, OperationCleanup, OperationResult, OrBroken, OperationSetup,
+    OperationRequest, ActiveTasksStorage, OperationReachability, ReachabilityResult,
+    InputBundle, Input, ManageInput, Cache,
+};
+
+use bevy::prelude::{Component, Entity};
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The source half of a `.cached(cache)` wrapper around a [`Service`]. This
+/// node receives the request, consults the [`Cache`] living on `cache`, and
+/// either answers immediately from a cache hit, attaches to an already
+/// in-flight request for the same key, or forwards the request on to the
+/// wrapped provider. A fresh dispatch's response is routed to a
+/// [`CacheBounce`] instead of the real downstream target, so that it can be
+/// recorded in the cache and fanned out to every waiter for that key.
+pub(crate) struct OperateCache<Request, Response, Streams> {
+    provider: Entity,
+    cache: Entity,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, Streams)>,
+}
+
+impl<Request, Response, Streams> OperateCache<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone + Eq + Hash,
+    Response: 'static + Send + Sync + Clone,
+{
+    pub(crate) fn new(
+        provider: Service<Request, Response, Streams>,
+        cache: Entity,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.provider(),
+            cache,
+            target,
+            _ignore: Default::default(),
+        }
+    }
+}
+
+impl<Request, Response, Streams> Operation for OperateCache<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone + Eq + Hash,
+    Response: 'static + Send + Sync + Clone,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.get_entity_mut(self.target).or_broken()?
+            .insert(SingleInputStorage::new(source));
+
+        if world.get_entity(self.cache).or_broken()?.get::<Cache<Request, Response>>().is_none() {
+            world.entity_mut(self.cache).insert(Cache::<Request, Response>::default());
+        }
+
+        let bounce = world.spawn(()).id();
+        CacheBounce::<Request, Response>::new(source, self.cache)
+            .setup(OperationSetup { source: bounce, world })?;
+
+        world.entity_mut(source).insert((
+            InputBundle::<Request>::new(),
+            ProviderStorage(self.provider),
+            CacheEntityStorage(self.cache),
+            CacheTargetStorage(self.target),
+            SingleTargetStorage(bounce),
+            PendingKeyStorage::<Request>::default(),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let bounce = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let provider = source_mut.get::<ProviderStorage>().or_broken()?.0;
+        let cache_entity = source_mut.get::<CacheEntityStorage>().or_broken()?.0;
+        let target = source_mut.get::<CacheTargetStorage>().or_broken()?.0;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        let hit = world.get_entity(cache_entity).or_broken()?
+            .get::<Cache<Request, Response>>().or_broken()?
+            .hit(&request);
+
+        if let Some(response) = hit {
+            return world.get_entity_mut(target).or_broken()?.give_input(session, response, roster);
+        }
+
+        let already_in_flight = {
+            let mut cache_mut = world.get_entity_mut(cache_entity).or_broken()?;
+            let mut cache = cache_mut.get_mut::<Cache<Request, Response>>().or_broken()?;
+            let already_in_flight = cache.is_in_flight(&request);
+            cache.add_waiter(request.clone(), session, target);
+            already_in_flight
+        };
+
+        if already_in_flight {
+            // Another session is already waiting on the same key; it will
+            // deliver our response once that attempt finishes.
+            return Ok(());
+        }
+
+        world.get_entity_mut(source).or_broken()?
+            .get_mut::<PendingKeyStorage<Request>>().or_broken()?
+            .pending.insert(session, request.clone());
+
+        dispatch_service(ServiceRequest {
+            provider,
+            target: bounce,
+            operation: OperationRequest { source, world, roster },
+        });
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Request>()?;
+        ActiveTasksStorage::cleanup(clean)
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        if reachability.has_input::<Request>()? {
+            return Ok(true);
+        }
+        if ActiveTasksStorage::contains_session(reachability)? {
+            return Ok(true);
+        }
+        SingleInputStorage::is_reachable(reachability)
+    }
+}
+
+#[derive(Component)]
+struct ProviderStorage(Entity);
+
+#[derive(Component)]
+struct CacheEntityStorage(Entity);
+
+#[derive(Component)]
+struct CacheTargetStorage(Entity);
+
+/// Remembers which request a session sent out to the wrapped provider, so
+/// [`CacheBounce`] can recover the cache key once the response comes back.
+#[derive(Component)]
+struct PendingKeyStorage<Request> {
+    pending: HashMap<Entity, Request>,
+}
+
+impl<Request> Default for PendingKeyStorage<Request> {
+    fn default() -> Self {
+        Self { pending: HashMap::new() }
+    }
+}
+
+/// The downstream half of a `.cached(cache)` wrapper. Receives the response
+/// from a fresh dispatch, records it in the [`Cache`], and fans it out to
+/// every session that was waiting on the same key, including the one that
+/// triggered the dispatch.
+pub(crate) struct CacheBounce<Request, Response> {
+    cache_source: Entity,
+    cache: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response)>,
+}
+
+impl<Request, Response> CacheBounce<Request, Response> {
+    fn new(cache_source: Entity, cache: Entity) -> Self {
+        Self { cache_source, cache, _ignore: Default::default() }
+    }
+}
+
+impl<Request, Response> Operation for CacheBounce<Request, Response>
+where
+    Request: 'static + Send + Sync + Clone + Eq + Hash,
+    Response: 'static + Send + Sync + Clone,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.entity_mut(source).insert((
+            CacheBounceSource(self.cache_source),
+            CacheBounceCache(self.cache),
+            InputBundle::<Response>::new(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let cache_source = source_mut.get::<CacheBounceSource>().or_broken()?.0;
+        let cache_entity = source_mut.get::<CacheBounceCache>().or_broken()?.0;
+        let Input { session, data: response } = source_mut.take_input::<Response>()?;
+
+        let request = world.get_entity_mut(cache_source).or_broken()?
+            .get_mut::<PendingKeyStorage<Request>>().or_broken()?
+            .pending.remove(&session).or_broken()?;
+
+        let waiters = world.get_entity_mut(cache_entity).or_broken()?
+            .get_mut::<Cache<Request, Response>>().or_broken()?
+            .complete(request, session, response.clone());
+
+        for (waiter_session, waiter_target) in waiters {
+            world.get_entity_mut(waiter_target).or_broken()?
+                .give_input(waiter_session, response.clone(), roster)?;
+        }
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Response>()?;
+        clean.notify_cleaned()
+    }
+
+    fn is_reachable(mut reachability: OperationReachability) -> ReachabilityResult {
+        reachability.has_input::<Response>()
+    }
+}
+
+#[derive(Component)]
+struct CacheBounceSource(Entity);
+
+#[derive(Component)]
+struct CacheBounceCache(Entity);
@@ -0,0 +1,152 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Operation, SingleTargetStorage, Service, ServiceRequest, SingleInputStorage,
+    dispatch_service, Cancellation, ManageCancellation, OperationCleanup,
+    OperationResult, OrBroken, OperationSetup, OperationRequest,
+    ActiveTasksStorage, OperationReachability, ReachabilityResult,
+    InputBundle, Input, ManageInput,
+};
+
+use bevy::prelude::{Component, Entity};
+
+/// A predicate that gates whether a request is allowed to reach the provider
+/// that a `.filter(~)` wraps, in the spirit of `tower-filter`.
+///
+/// Returning `Ok` lets the (possibly transformed) request continue on to the
+/// wrapped provider. Returning `Err` cancels the delivery instead, with the
+/// error becoming the reason attached to the [`Filtered`](crate::Filtered)
+/// cancellation.
+pub trait FilterPredicate<Request>: 'static + Send + Sync {
+    fn filter(&mut self, request: Request) -> Result<Request, Option<anyhow::Error>>;
+}
+
+impl<Request, F> FilterPredicate<Request> for F
+where
+    F: FnMut(Request) -> Result<Request, Option<anyhow::Error>> + 'static + Send + Sync,
+{
+    fn filter(&mut self, request: Request) -> Result<Request, Option<anyhow::Error>> {
+        self(request)
+    }
+}
+
+/// An intermediate operation, analogous to [`OperateService`](crate::OperateService),
+/// that runs a [`FilterPredicate`] on each incoming request before forwarding
+/// it to the wrapped provider.
+pub(crate) struct OperateFilter<F, Request, Response, Streams> {
+    provider: Entity,
+    predicate: F,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, Streams)>,
+}
+
+impl<F, Request, Response, Streams> OperateFilter<F, Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+{
+    pub(crate) fn new(
+        provider: Service<Request, Response, Streams>,
+        predicate: F,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.provider(),
+            predicate,
+            target,
+            _ignore: Default::default(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ProviderStorage(Entity);
+
+#[derive(Component)]
+struct FilterStorage<F> {
+    f: Option<F>,
+}
+
+impl<F, Request, Response, Streams> Operation for OperateFilter<F, Request, Response, Streams>
+where
+    F: FilterPredicate<Request> + 'static + Send + Sync,
+    Request: 'static + Send + Sync,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.get_entity_mut(self.target).or_broken()?
+            .insert(SingleInputStorage::new(source));
+
+        world.entity_mut(source).insert((
+            InputBundle::<Request>::new(),
+            ProviderStorage(self.provider),
+            FilterStorage { f: Some(self.predicate) },
+            SingleTargetStorage(self.target),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let provider = source_mut.get::<ProviderStorage>().or_broken()?.0;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        let mut predicate = source_mut.get_mut::<FilterStorage<F>>().or_broken()?
+            .f.take().or_broken()?;
+        let outcome = predicate.filter(request);
+        world.get_entity_mut(source).or_broken()?
+            .get_mut::<FilterStorage<F>>().or_broken()?
+            .f = Some(predicate);
+
+        match outcome {
+            Ok(request) => {
+                let mut source_mut = world.get_entity_mut(source).or_broken()?;
+                source_mut.give_input(session, request, roster)?;
+
+                dispatch_service(ServiceRequest {
+                    provider,
+                    target,
+                    operation: OperationRequest { source, world, roster },
+                });
+            }
+            Err(reason) => {
+                world.get_entity_mut(source).or_broken()?
+                    .emit_cancel(session, Cancellation::filtered(source, reason), roster);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Request>()?;
+        ActiveTasksStorage::cleanup(clean)
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        if reachability.has_input::<Request>()? {
+            return Ok(true);
+        }
+        if ActiveTasksStorage::contains_session(reachability)? {
+            return Ok(true);
+        }
+        SingleInputStorage::is_reachable(reachability)
+    }
+}
@@ -20,11 +20,12 @@ use crate::{
     SingleTargetStorage, StreamPack, Input, ManageInput, OperationCleanup,
     CallBlockingMap, CallAsyncMap, SingleInputStorage, OperationResult,
     OrBroken, OperationSetup, OperationRequest, OperateTask, ActiveTasksStorage,
-    OperationReachability, ReachabilityResult, InputBundle,
+    OperationReachability, ReachabilityResult, InputBundle, CancellationToken,
+    TaskCompletionHook,
 };
 
 use bevy::{
-    prelude::{Component, Entity, Bundle},
+    prelude::{Component, Entity, Bundle, World},
     tasks::AsyncComputeTaskPool,
 };
 
@@ -119,6 +120,8 @@ where
 {
     storage: AsyncMapStorage<F>,
     target: SingleTargetStorage,
+    concurrency_limit: Option<ConcurrencyLimitStorage>,
+    pending: PendingRequestsStorage<Request>,
     #[bundle(ignore)]
     _ignore: std::marker::PhantomData<(Request, Task, Streams)>,
 }
@@ -134,9 +137,24 @@ where
         Self {
             storage: AsyncMapStorage { f: Some(f) },
             target: SingleTargetStorage::new(target),
+            concurrency_limit: None,
+            pending: PendingRequestsStorage::default(),
             _ignore: Default::default(),
         }
     }
+
+    /// Like [`Self::new`], but caps the node at `max_in_flight` spawned
+    /// tasks. Once that many tasks are active, incoming requests are queued
+    /// instead of spawned immediately, and are dequeued in order as
+    /// in-flight tasks finish. Use this for services that touch a limited
+    /// resource (a database connection pool, a piece of hardware) that
+    /// cannot serve unbounded concurrent work.
+    pub(crate) fn new_with_concurrency_limit(target: Entity, f: F, max_in_flight: usize) -> Self {
+        Self {
+            concurrency_limit: Some(ConcurrencyLimitStorage(max_in_flight)),
+            ..Self::new(target, f)
+        }
+    }
 }
 
 #[derive(Component)]
@@ -144,6 +162,109 @@ struct AsyncMapStorage<F> {
     f: Option<F>,
 }
 
+/// The maximum number of tasks an [`OperateAsyncMap`] will keep in flight at
+/// once. Present only if the node was built with
+/// [`OperateAsyncMap::new_with_concurrency_limit`].
+#[derive(Component)]
+struct ConcurrencyLimitStorage(usize);
+
+/// Requests that arrived while an [`OperateAsyncMap`] with a
+/// [`ConcurrencyLimitStorage`] was already at its limit, queued in arrival
+/// order and dequeued as in-flight tasks finish.
+#[derive(Component)]
+struct PendingRequestsStorage<Request> {
+    pending: std::collections::VecDeque<(Entity, Request)>,
+}
+
+impl<Request> Default for PendingRequestsStorage<Request> {
+    fn default() -> Self {
+        Self { pending: std::collections::VecDeque::new() }
+    }
+}
+
+/// Spawn the task for one `request`, shared between the first attempt in
+/// [`Operation::execute`] and the concurrency-limited continuation in
+/// [`on_async_map_task_complete`].
+fn spawn_async_map_task<F, Request, Task, Streams>(
+    source: Entity,
+    session: Entity,
+    request: Request,
+    world: &mut World,
+    roster: &mut OperationRoster,
+) -> OperationResult
+where
+    F: CallAsyncMap<Request, Task, Streams> + 'static + Send + Sync,
+    Task: Future + 'static + Send + Sync,
+    Request: 'static + Send + Sync,
+    Task::Output: 'static + Send + Sync,
+    Streams: StreamPack,
+{
+    let sender = world.get_resource_or_insert_with(|| ChannelQueue::new()).sender.clone();
+    let mut source_mut = world.get_entity_mut(source).or_broken()?;
+    let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+    let mut f = source_mut.get_mut::<AsyncMapStorage<F>>().or_broken()?
+        .f.take().or_broken()?;
+
+    let channel = InnerChannel::new(source, session, sender.clone());
+    let channel = channel.into_specific(&world)?;
+
+    // NOTE: `InnerChannel`/`AsyncMap` do not yet expose this token to the
+    // running service as `channel.cancellation()` in this tree; once
+    // they do, clone `cancellation` into `channel` here. For now it is
+    // still threaded through `OperateTask` so `Cancel`/teardown trigger
+    // it and wake anything already suspended on it.
+    let cancellation = CancellationToken::new();
+
+    let task = AsyncComputeTaskPool::get().spawn(f.call(AsyncMap { request, channel }));
+    world.get_entity_mut(source).or_broken()?
+        .get_mut::<AsyncMapStorage<F>>().or_broken()?
+        .f = Some(f);
+
+    let task_source = world.spawn(()).id();
+    OperateTask::new_with_cancellation_token(
+        task_source, session, source, target, task, None, sender, cancellation,
+    ).setup(OperationSetup { source: task_source, world });
+
+    if world.get::<ConcurrencyLimitStorage>(source).is_some() {
+        world.entity_mut(source).insert(
+            TaskCompletionHook(on_async_map_task_complete::<F, Request, Task, Streams>)
+        );
+    }
+
+    roster.queue(task_source);
+    Ok(())
+}
+
+/// Run whenever one of an [`OperateAsyncMap`]'s tasks finishes: if the node
+/// is below its [`ConcurrencyLimitStorage`] limit and has a pending request
+/// queued up, dequeue and spawn it.
+fn on_async_map_task_complete<F, Request, Task, Streams>(
+    source: Entity,
+    world: &mut World,
+    roster: &mut OperationRoster,
+)
+where
+    F: CallAsyncMap<Request, Task, Streams> + 'static + Send + Sync,
+    Task: Future + 'static + Send + Sync,
+    Request: 'static + Send + Sync,
+    Task::Output: 'static + Send + Sync,
+    Streams: StreamPack,
+{
+    let Some(mut source_mut) = world.get_entity_mut(source) else { return };
+    let Some(limit) = source_mut.get::<ConcurrencyLimitStorage>().map(|l| l.0) else { return };
+    let Some(in_flight) = source_mut.get::<ActiveTasksStorage>().map(|t| t.list.len()) else { return };
+    if in_flight >= limit {
+        return;
+    }
+
+    let next = source_mut.get_mut::<PendingRequestsStorage<Request>>()
+        .and_then(|mut pending| pending.pending.pop_front());
+
+    if let Some((session, request)) = next {
+        let _ = spawn_async_map_task::<F, Request, Task, Streams>(source, session, request, world, roster);
+    }
+}
+
 impl<F, Request, Task, Streams> Operation for OperateAsyncMap<F, Request, Task, Streams>
 where
     F: CallAsyncMap<Request, Task, Streams> + 'static + Send + Sync,
@@ -167,30 +288,28 @@ where
     fn execute(
         OperationRequest { source, world, roster }: OperationRequest,
     ) -> OperationResult {
-        let sender = world.get_resource_or_insert_with(|| ChannelQueue::new()).sender.clone();
         let mut source_mut = world.get_entity_mut(source).or_broken()?;
         let Input { session, data: request } = source_mut.take_input::<Request>()?;
-        let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
-        let mut f = source_mut.get_mut::<AsyncMapStorage<F>>().or_broken()?
-            .f.take().or_broken()?;
-
-        let channel = InnerChannel::new(source, session, sender.clone());
-        let channel = channel.into_specific(&world)?;
 
-        let task = AsyncComputeTaskPool::get().spawn(f.call(AsyncMap { request, channel }));
-        world.get_entity_mut(source).or_broken()?
-            .get_mut::<AsyncMapStorage<F>>().or_broken()?
-            .f = Some(f);
+        if let Some(limit) = source_mut.get::<ConcurrencyLimitStorage>().map(|l| l.0) {
+            let in_flight = source_mut.get::<ActiveTasksStorage>().or_broken()?.list.len();
+            if in_flight >= limit {
+                source_mut.get_mut::<PendingRequestsStorage<Request>>().or_broken()?
+                    .pending.push_back((session, request));
+                return Ok(());
+            }
+        }
 
-        let task_source = world.spawn(()).id();
-        OperateTask::new(task_source, session, source, target, task, None, sender)
-            .setup(OperationSetup { source: task_source, world });
-        roster.queue(task_source);
-        Ok(())
+        spawn_async_map_task::<F, Request, Task, Streams>(source, session, request, world, roster)
     }
 
     fn cleanup(mut clean: OperationCleanup) -> OperationResult {
         clean.cleanup_inputs::<Request>()?;
+        if let Some(mut pending) = clean.world.get_mut::<PendingRequestsStorage<Request>>(clean.source) {
+            // Dispose of every request still queued behind the concurrency
+            // limit for this session so it doesn't leak.
+            pending.pending.retain(|(session, _)| *session != clean.session);
+        }
         ActiveTasksStorage::cleanup(clean)
     }
 
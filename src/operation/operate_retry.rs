@@ -0,0 +1,224 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Operation, SingleTargetStorage, Service, ServiceRequest, SingleInputStorage,
+    dispatch_service, OperationCleanup, OperationResult, OrBroken, OperationSetup,
+    OperationRequest, ActiveTasksStorage, OperationReachability, ReachabilityResult,
+    InputBundle, Input, ManageInput, RetryPolicy,
+};
+
+use bevy::prelude::{Component, Entity};
+
+use std::collections::HashMap;
+
+/// The source half of a `.retry(policy)` wrapper around a [`Service`]. This
+/// node receives the original request, remembers it, and forwards it on to
+/// the wrapped provider. Its output is routed to a [`RetryBounce`] instead of
+/// the real downstream target, so that the response can be judged by the
+/// policy before it is allowed to continue.
+pub(crate) struct OperateRetry<Request, Response, Streams, P> {
+    provider: Entity,
+    policy: P,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, Streams)>,
+}
+
+impl<Request, Response, Streams, P> OperateRetry<Request, Response, Streams, P>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    P: RetryPolicy<Request, Response>,
+{
+    pub(crate) fn new(
+        provider: Service<Request, Response, Streams>,
+        policy: P,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.provider(),
+            policy,
+            target,
+            _ignore: Default::default(),
+        }
+    }
+}
+
+impl<Request, Response, Streams, P> Operation for OperateRetry<Request, Response, Streams, P>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    P: RetryPolicy<Request, Response>,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.get_entity_mut(self.target).or_broken()?
+            .insert(SingleInputStorage::new(source));
+
+        let bounce = world.spawn(()).id();
+        RetryBounce::<Request, Response, P>::new(source, self.target)
+            .setup(OperationSetup { source: bounce, world })?;
+
+        world.entity_mut(source).insert((
+            InputBundle::<Request>::new(),
+            ProviderStorage(self.provider),
+            SingleTargetStorage(bounce),
+            RetryStorage::<Request, Response, P>::new(self.policy),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let bounce = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let provider = source_mut.get::<ProviderStorage>().or_broken()?.0;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        source_mut.get_mut::<RetryStorage<Request, Response, P>>().or_broken()?
+            .remember(session, request.clone());
+        source_mut.give_input(session, request, roster)?;
+
+        dispatch_service(ServiceRequest {
+            provider,
+            target: bounce,
+            operation: OperationRequest { source, world, roster },
+        });
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Request>()?;
+        ActiveTasksStorage::cleanup(clean)
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        if reachability.has_input::<Request>()? {
+            return Ok(true);
+        }
+        if ActiveTasksStorage::contains_session(reachability)? {
+            return Ok(true);
+        }
+        SingleInputStorage::is_reachable(reachability)
+    }
+}
+
+#[derive(Component)]
+struct ProviderStorage(Entity);
+
+/// Remembers the per-session attempt state and the last request that was sent
+/// out, so [`RetryBounce`] can hand both back to the policy once a response
+/// comes in.
+#[derive(Component)]
+struct RetryStorage<Request, Response, P: RetryPolicy<Request, Response>> {
+    policy: P,
+    attempts: HashMap<Entity, (P::State, Request)>,
+    _ignore: std::marker::PhantomData<Response>,
+}
+
+impl<Request, Response, P: RetryPolicy<Request, Response>> RetryStorage<Request, Response, P> {
+    fn new(policy: P) -> Self {
+        Self { policy, attempts: HashMap::new(), _ignore: Default::default() }
+    }
+
+    fn remember(&mut self, session: Entity, request: Request) {
+        let state = self.attempts.remove(&session)
+            .map(|(state, _)| state)
+            .unwrap_or_else(|| self.policy.init_state());
+        self.attempts.insert(session, (state, request));
+    }
+}
+
+/// The downstream half of a `.retry(policy)` wrapper. This node receives the
+/// response that came back from the wrapped provider, consults the policy,
+/// and either re-triggers [`OperateRetry`] with a new request or forwards the
+/// response on to the real target.
+pub(crate) struct RetryBounce<Request, Response, P> {
+    retry_source: Entity,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, P)>,
+}
+
+impl<Request, Response, P> RetryBounce<Request, Response, P> {
+    fn new(retry_source: Entity, target: Entity) -> Self {
+        Self { retry_source, target, _ignore: Default::default() }
+    }
+}
+
+impl<Request, Response, P> Operation for RetryBounce<Request, Response, P>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    P: RetryPolicy<Request, Response> + 'static + Send + Sync,
+{
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.entity_mut(source).insert((
+            RetrySourceStorage(self.retry_source),
+            SingleTargetStorage(self.target),
+            InputBundle::<Response>::new(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let retry_source = source_mut.get::<RetrySourceStorage>().or_broken()?.0;
+        let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let Input { session, data: response } = source_mut.take_input::<Response>()?;
+
+        let next_request = {
+            let mut retry_mut = world.get_entity_mut(retry_source).or_broken()?;
+            let mut retry = retry_mut.get_mut::<RetryStorage<Request, Response, P>>().or_broken()?;
+            let Some((state, attempted)) = retry.attempts.get_mut(&session) else {
+                return world.get_entity_mut(target).or_broken()?
+                    .give_input(session, response, roster);
+            };
+            retry.policy.on_response(state, attempted, &response)
+        };
+
+        match next_request {
+            Some(next) => {
+                world.get_entity_mut(retry_source).or_broken()?
+                    .give_input(session, next, roster)?;
+                roster.queue(retry_source);
+                Ok(())
+            }
+            None => {
+                world.get_entity_mut(retry_source).or_broken()?
+                    .get_mut::<RetryStorage<Request, Response, P>>().or_broken()?
+                    .attempts.remove(&session);
+                world.get_entity_mut(target).or_broken()?
+                    .give_input(session, response, roster)
+            }
+        }
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        clean.cleanup_inputs::<Response>()?;
+        clean.notify_cleaned()
+    }
+
+    fn is_reachable(mut reachability: OperationReachability) -> ReachabilityResult {
+        reachability.has_input::<Response>()
+    }
+}
+
+#[derive(Component)]
+struct RetrySourceStorage(Entity);
@@ -25,11 +25,14 @@ use crate::{
 
 use bevy::{
     prelude::{Component, Entity, World, Query},
-    ecs::system::SystemState,
+    ecs::system::{Command, SystemState},
 };
 
+use std::collections::VecDeque;
+
 pub(crate) struct OperateService<Request> {
     provider: Entity,
+    fallback: Option<Entity>,
     target: Entity,
     _ignore: std::marker::PhantomData<Request>,
 }
@@ -41,6 +44,23 @@ impl<Request: 'static + Send + Sync> OperateService<Request> {
     ) -> Self {
         Self {
             provider: provider.get(),
+            fallback: None,
+            target,
+            _ignore: Default::default(),
+        }
+    }
+
+    /// Like [`Self::new`], but if `provider` ever becomes unavailable, the
+    /// delivery is re-routed to `fallback` instead of being cancelled, like
+    /// actix-web's default service for unmatched routes.
+    pub(crate) fn with_fallback<Response, Streams>(
+        provider: Service<Request, Response, Streams>,
+        fallback: Service<Request, Response, Streams>,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.get(),
+            fallback: Some(fallback.get()),
             target,
             _ignore: Default::default(),
         }
@@ -55,6 +75,7 @@ impl<Request: 'static + Send + Sync> Operation for OperateService<Request> {
         world.entity_mut(source).insert((
             InputBundle::<Request>::new(),
             ProviderStorage(self.provider),
+            FallbackStorage(self.fallback),
             SingleTargetStorage(self.target),
             ActiveTasksStorage::default(),
         ));
@@ -66,11 +87,33 @@ impl<Request: 'static + Send + Sync> Operation for OperateService<Request> {
         let target = source_ref.get::<SingleTargetStorage>().or_broken()?.0;
         let provider = source_ref.get::<ProviderStorage>().or_broken()?.0;
 
+        if operation.world.get::<ConcurrencyLimit>(provider).is_some() {
+            let saturated = operation.world.get::<ConcurrencyLimit>(provider)
+                .or_broken()?.available == 0;
+
+            if saturated {
+                if operation.world.get::<LoadShed>(provider).is_some() {
+                    operation.roster.cancel(
+                        Cancel::service_unavailable(operation.source, provider)
+                    );
+                    return Ok(());
+                }
+
+                operation.world.get_mut::<ConcurrencyLimit>(provider).or_broken()?
+                    .queue.push_back(operation.source);
+                return Ok(());
+            }
+
+            operation.world.get_mut::<ConcurrencyLimit>(provider).or_broken()?
+                .available -= 1;
+        }
+
         dispatch_service(ServiceRequest { provider, target, operation });
         Ok(())
     }
 
     fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        release_permit_and_pump(&mut clean)?;
         clean.cleanup_inputs::<Request>()?;
         ActiveTasksStorage::cleanup(clean)
     }
@@ -89,17 +132,125 @@ impl<Request: 'static + Send + Sync> Operation for OperateService<Request> {
 #[derive(Component)]
 struct ProviderStorage(Entity);
 
+/// The fallback provider to re-route to, set by [`Service::or_else`](crate::Service::or_else),
+/// if the primary provider this node depends on ever becomes unavailable.
+#[derive(Component)]
+struct FallbackStorage(Option<Entity>);
+
 pub(crate) fn cancel_service(
     cancelled_provider: Entity,
     world: &mut World,
     roster: &mut OperationRoster,
 ) {
-    let mut providers_state: SystemState<Query<(Entity, &ProviderStorage)>> =
+    let mut providers_state: SystemState<Query<(Entity, &ProviderStorage, &FallbackStorage)>> =
         SystemState::new(world);
     let providers = providers_state.get(world);
-    for (source, ProviderStorage(provider)) in &providers {
+
+    let mut reroute = Vec::new();
+    let mut give_up = Vec::new();
+    for (source, ProviderStorage(provider), FallbackStorage(fallback)) in &providers {
         if *provider == cancelled_provider {
-            roster.cancel(Cancel::service_unavailable(source, cancelled_provider));
+            match fallback {
+                Some(fallback) => reroute.push((source, *fallback)),
+                None => give_up.push(source),
+            }
+        }
+    }
+
+    for (source, fallback) in reroute {
+        if let Some(mut source_mut) = world.get_entity_mut(source) {
+            source_mut.insert(ProviderStorage(fallback));
+            roster.queue(source);
+        }
+    }
+
+    for source in give_up {
+        roster.cancel(Cancel::service_unavailable(source, cancelled_provider));
+    }
+}
+
+/// A shared permit pool for a provider entity, used by `.concurrency_limit(~)`
+/// to cap how many requests the provider runs at the same time, regardless of
+/// how many distinct delivery labels are in play.
+#[derive(Component)]
+pub(crate) struct ConcurrencyLimit {
+    available: usize,
+    queue: VecDeque<Entity>,
+}
+
+impl ConcurrencyLimit {
+    fn new(max: usize) -> Self {
+        Self { available: max, queue: VecDeque::new() }
+    }
+}
+
+fn release_permit_and_pump(clean: &mut OperationCleanup) -> OperationResult {
+    let source_ref = clean.world.get_entity(clean.source).or_broken()?;
+    let Some(provider) = source_ref.get::<ProviderStorage>().map(|p| p.0) else {
+        return Ok(());
+    };
+
+    let Some(mut limit) = clean.world.get_mut::<ConcurrencyLimit>(provider) else {
+        return Ok(());
+    };
+
+    limit.available += 1;
+    if let Some(next) = limit.queue.pop_front() {
+        clean.roster.queue(next);
+    }
+
+    Ok(())
+}
+
+/// A one-shot command that gives a provider entity a shared
+/// [`ConcurrencyLimit`] permit pool the first time `.concurrency_limit(~)` is
+/// applied to it. Later calls for the same provider just reuse the pool that
+/// is already there.
+pub(crate) struct InitConcurrencyLimit {
+    provider: Entity,
+    max: usize,
+}
+
+impl InitConcurrencyLimit {
+    pub(crate) fn new(provider: Entity, max: usize) -> Self {
+        Self { provider, max }
+    }
+}
+
+impl Command for InitConcurrencyLimit {
+    fn apply(self, world: &mut World) {
+        if let Some(mut provider_mut) = world.get_entity_mut(self.provider) {
+            if provider_mut.get::<ConcurrencyLimit>().is_none() {
+                provider_mut.insert(ConcurrencyLimit::new(self.max));
+            }
+        }
+    }
+}
+
+/// A marker on a provider entity that tells [`OperateService::execute`] to
+/// shed new requests instead of queueing them once the provider's
+/// [`ConcurrencyLimit`] is saturated. Applied by `.load_shed()`.
+#[derive(Component)]
+pub(crate) struct LoadShed;
+
+/// A one-shot command that marks a provider entity as load-shedding the first
+/// time `.load_shed()` is applied to it.
+pub(crate) struct InitLoadShed {
+    provider: Entity,
+}
+
+impl InitLoadShed {
+    pub(crate) fn new(provider: Entity) -> Self {
+        Self { provider }
+    }
+}
+
+impl Command for InitLoadShed {
+    fn apply(self, world: &mut World) {
+        if let Some(mut provider_mut) = world.get_entity_mut(self.provider) {
+            if provider_mut.get::<LoadShed>().is_none() {
+                provider_mut.insert(LoadShed);
+            }
         }
     }
 }
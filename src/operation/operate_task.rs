@@ -17,7 +17,7 @@
 
 use bevy::{
     prelude::{Component, Entity, World, Resource, Bundle},
-    tasks::{Task as BevyTask, AsyncComputeTaskPool},
+    tasks::{Task as BevyTask, AsyncComputeTaskPool, IoTaskPool, TaskPool, TaskPoolBuilder},
 };
 
 use std::{
@@ -25,10 +25,14 @@ use std::{
     future::Future,
     pin::Pin,
     task::Context,
-    sync::Arc,
+    sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}},
+    time::Duration,
 };
 
-use futures::task::{waker_ref, ArcWake};
+use futures::{
+    task::{waker_ref, ArcWake, noop_waker},
+    future::{BoxFuture, FutureExt},
+};
 
 use crossbeam::channel::{unbounded, Sender as CbSender, Receiver as CbReceiver};
 
@@ -38,9 +42,292 @@ use crate::{
     SingleTargetStorage, OperationRoster, Blocker, ManageInput,
     OperationSetup, OperationRequest, OperationResult, Operation,
     OrBroken, BlockerStorage, OperationCleanup, ChannelQueue,
-    OperationReachability, ReachabilityResult,
+    OperationReachability, ReachabilityResult, Canceled,
 };
 
+/// Adapted from deno_core's `Cancelable<F>`: wraps a task future so that a
+/// [`Cancelable`] observes its session's cancellation cooperatively instead
+/// of being aborted out from under it. Build one with [`TaskCancelSignal::wrap`].
+///
+/// Once the signal backing this future is triggered, the next poll resolves
+/// to `Err(`[`Canceled`]`)` instead of polling the wrapped future again, no
+/// matter how much progress that future had left to make.
+pub struct Cancelable<F> {
+    future: F,
+    signal: TaskCancelSignal,
+}
+
+impl<F: Future> Future for Cancelable<F> {
+    type Output = Result<F::Output, Canceled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.signal.is_canceled() {
+            return Poll::Ready(Err(Canceled));
+        }
+
+        // SAFETY: `future` is never moved out of `self`, so projecting a
+        // pinned reference to it is sound.
+        let future = unsafe { self.map_unchecked_mut(|s| &mut s.future) };
+        future.poll(cx).map(Ok)
+    }
+}
+
+/// A cloneable handle that a task can use to check whether its session has
+/// been cancelled, so that long-running work can checkpoint and bail out on
+/// its own terms. Obtain one with [`Self::new`] and wrap the task's future
+/// with [`Self::wrap`] before spawning it.
+#[derive(Clone, Default)]
+pub struct TaskCancelSignal(Arc<AtomicBool>);
+
+impl TaskCancelSignal {
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Wrap a future so that it resolves to `Err(`[`Canceled`]`)` the first
+    /// time it is polled after this signal has been triggered.
+    pub fn wrap<F: Future>(&self, future: F) -> Cancelable<F> {
+        Cancelable { future, signal: self.clone() }
+    }
+
+    /// Check whether this signal has been triggered.
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn trigger(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A cooperative cancellation handle modeled on the actor-style
+/// `CancellationToken` used in task runtimes (e.g. `tokio_util`). Unlike
+/// [`TaskCancelSignal`], which wraps a task's future from the outside and
+/// forces it to resolve early, a [`CancellationToken`] is meant to be cloned
+/// into the future itself (e.g. via
+/// [`AsyncMap`](crate::AsyncMap)'s channel) so a service can check
+/// [`Self::is_triggered`] between steps, or `.await` [`Self::cancelled`] at
+/// an await point to wake up as soon as its session is cancelled instead of
+/// only finding out the next time it happens to check.
+#[derive(Clone)]
+pub struct CancellationToken {
+    triggered: Arc<AtomicBool>,
+    notify: Arc<tokio::sync::Notify>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self {
+            triggered: Arc::new(AtomicBool::new(false)),
+            notify: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+
+    /// Check without blocking whether this token has been triggered.
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Ordering::Relaxed)
+    }
+
+    /// Suspend until this token is triggered. Resolves immediately if it has
+    /// already been triggered by the time this is called.
+    pub async fn cancelled(&self) {
+        if self.is_triggered() {
+            return;
+        }
+        self.notify.notified().await;
+    }
+
+    fn trigger(&self) {
+        self.triggered.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A backend that an async map can spawn its [`Future`] onto. Following the
+/// backend-abstraction pattern used for storage backends, this lets a map
+/// choose where its work actually runs instead of always landing on
+/// [`AsyncComputeTaskPool`], which is tuned for CPU-bound work and not a good
+/// fit for IO-bound futures.
+pub trait TaskExecutor: 'static + Send + Sync {
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T>;
+}
+
+/// Spawns onto Bevy's [`AsyncComputeTaskPool`], same as the hardcoded
+/// behavior this replaces. Suited to CPU-bound futures.
+#[derive(Clone, Copy, Default)]
+pub struct ComputePoolExecutor;
+
+impl TaskExecutor for ComputePoolExecutor {
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T> {
+        AsyncComputeTaskPool::get().spawn(future)
+    }
+}
+
+/// Spawns onto Bevy's [`IoTaskPool`]. Suited to IO-bound futures (network,
+/// disk) that should not occupy compute threads.
+#[derive(Clone, Copy, Default)]
+pub struct IoPoolExecutor;
+
+impl TaskExecutor for IoPoolExecutor {
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T> {
+        IoTaskPool::get().spawn(future)
+    }
+}
+
+/// Spawns onto a dedicated single-threaded [`TaskPool`] owned by this
+/// executor, so the future never competes with Bevy's shared compute or IO
+/// pools for a thread. Useful for futures that need to run serially relative
+/// to each other.
+#[derive(Clone)]
+pub struct LocalPoolExecutor {
+    pool: Arc<TaskPool>,
+}
+
+impl LocalPoolExecutor {
+    pub fn new() -> Self {
+        Self { pool: Arc::new(TaskPoolBuilder::new().num_threads(1).build()) }
+    }
+}
+
+impl Default for LocalPoolExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskExecutor for LocalPoolExecutor {
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T> {
+        self.pool.spawn(future)
+    }
+}
+
+/// Spawns onto its own run-queue that is only drained and polled once per
+/// configurable wall-clock `interval`, inspired by the smol-based throttling
+/// schedulers used in low-latency media pipelines. Bounds how often futures
+/// spawned this way wake the app up under high future churn, trading latency
+/// for a lower, predictable polling rate, instead of polling as eagerly as
+/// [`ComputePoolExecutor`]/[`IoPoolExecutor`] do.
+///
+/// Tasks spawned through this executor are still handed back as a normal
+/// [`BevyTask`], so they resolve their `Promise`/`Storage` targets through
+/// the usual [`OperateTask`] flush path like any other executor.
+#[derive(Clone)]
+pub struct ThrottledExecutor {
+    queue: Arc<Mutex<Vec<BoxFuture<'static, ()>>>>,
+}
+
+impl ThrottledExecutor {
+    /// Start a throttled executor whose background thread drains its
+    /// run-queue and polls every pending task once every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        let queue: Arc<Mutex<Vec<BoxFuture<'static, ()>>>> = Arc::new(Mutex::new(Vec::new()));
+        let thread_queue = queue.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Ok(mut pending) = thread_queue.lock() else { return };
+            if pending.is_empty() {
+                continue;
+            }
+
+            let waker = noop_waker();
+            let mut cx = Context::from_waker(&waker);
+            pending.retain_mut(|task| task.as_mut().poll(&mut cx).is_pending());
+        });
+
+        Self { queue }
+    }
+}
+
+impl TaskExecutor for ThrottledExecutor {
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T> {
+        let (sender, receiver) = crossbeam::channel::bounded(1);
+        self.queue.lock().unwrap().push(async move {
+            let _ = sender.send(future.await);
+        }.boxed());
+
+        AsyncComputeTaskPool::get().spawn(async move {
+            receiver.recv().expect("the throttled executor's background thread stopped")
+        })
+    }
+}
+
+/// Selects which [`TaskExecutor`] backend an async map should use. Stored as
+/// a [`DefaultTaskExecutor`] resource for the whole app, and can be
+/// overridden per-map with a [`TaskExecutorOverride`] component.
+#[derive(Clone)]
+pub enum TaskExecutorSelection {
+    Compute,
+    Io,
+    Local(LocalPoolExecutor),
+    /// Run on a [`ThrottledExecutor`] with the given tick interval.
+    Throttled(ThrottledExecutor),
+}
+
+impl Default for TaskExecutorSelection {
+    fn default() -> Self {
+        Self::Compute
+    }
+}
+
+impl TaskExecutorSelection {
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> BevyTask<T> {
+        match self {
+            Self::Compute => ComputePoolExecutor.spawn(future),
+            Self::Io => IoPoolExecutor.spawn(future),
+            Self::Local(pool) => pool.spawn(future),
+            Self::Throttled(pool) => pool.spawn(future),
+        }
+    }
+}
+
+/// The app-wide default [`TaskExecutorSelection`] used by async maps that do
+/// not have a [`TaskExecutorOverride`]. Defaults to
+/// [`TaskExecutorSelection::Compute`], matching the pre-existing hardcoded
+/// behavior.
+#[derive(Resource, Clone, Default)]
+pub struct DefaultTaskExecutor(pub TaskExecutorSelection);
+
+/// Overrides the [`DefaultTaskExecutor`] for one specific async map node.
+#[derive(Component, Clone)]
+pub struct TaskExecutorOverride(pub TaskExecutorSelection);
+
+/// Resolve which [`TaskExecutorSelection`] an async map at `source` should
+/// use: its own [`TaskExecutorOverride`] if it has one, otherwise the app's
+/// [`DefaultTaskExecutor`].
+pub(crate) fn resolve_task_executor(source: Entity, world: &World) -> TaskExecutorSelection {
+    if let Some(TaskExecutorOverride(executor)) = world.get::<TaskExecutorOverride>(source) {
+        return executor.clone();
+    }
+
+    world.get_resource::<DefaultTaskExecutor>()
+        .map(|default| default.0.clone())
+        .unwrap_or_default()
+}
+
 struct JobWaker {
     sender: CbSender<Entity>,
     entity: Entity,
@@ -80,6 +367,26 @@ struct TaskOwnerStorage(Entity);
 #[derive(Component)]
 pub(crate) struct PollTask(pub(crate) fn(Entity, &mut World, &mut OperationRoster));
 
+/// Held on an async map node that limits its in-flight task count (see
+/// [`crate::OperateAsyncMap`]): run whenever one of the node's tasks
+/// finishes, so a pending request queued behind the concurrency limit can be
+/// dequeued and spawned.
+#[derive(Component, Clone, Copy)]
+pub(crate) struct TaskCompletionHook(pub(crate) fn(Entity, &mut World, &mut OperationRoster));
+
+/// Holds the [`TaskCancelSignal`] that a task's future was wrapped with, if
+/// any, so that [`OperateTask::cleanup`] can trigger it just before the task
+/// is forcibly aborted.
+#[derive(Component)]
+struct TaskCancelSignalStorage(TaskCancelSignal);
+
+/// Holds the [`CancellationToken`] that was cloned into a task's future, if
+/// any, so that [`OperateTask::cleanup`] can trigger it and wake up anything
+/// suspended on [`CancellationToken::cancelled`] before the task is forcibly
+/// aborted.
+#[derive(Component)]
+struct CancellationTokenStorage(CancellationToken);
+
 #[derive(Bundle)]
 pub(crate) struct OperateTask<Response: 'static + Send + Sync> {
     session: TaskSessionStorage,
@@ -87,6 +394,8 @@ pub(crate) struct OperateTask<Response: 'static + Send + Sync> {
     target: SingleTargetStorage,
     task: TaskStorage<Response>,
     blocker: BlockerStorage,
+    signal: Option<TaskCancelSignalStorage>,
+    cancellation: Option<CancellationTokenStorage>,
 }
 
 impl<Response: 'static + Send + Sync> OperateTask<Response> {
@@ -103,6 +412,46 @@ impl<Response: 'static + Send + Sync> OperateTask<Response> {
             target: SingleTargetStorage(target),
             task: TaskStorage(task),
             blocker: BlockerStorage(blocker),
+            signal: None,
+            cancellation: None,
+        }
+    }
+
+    /// Like [`Self::new`], but for a task whose future was wrapped with
+    /// [`TaskCancelSignal::wrap`]. When the session is cleaned up, `signal` is
+    /// triggered and the task is then forcibly aborted straight away; cleanup
+    /// does not poll the future again or wait for it to resolve first.
+    pub(crate) fn new_cancelable(
+        session: Entity,
+        owner: Entity,
+        target: Entity,
+        task: BevyTask<Response>,
+        blocker: Option<Blocker>,
+        signal: TaskCancelSignal,
+    ) -> Self {
+        Self {
+            signal: Some(TaskCancelSignalStorage(signal)),
+            ..Self::new(session, owner, target, task, blocker)
+        }
+    }
+
+    /// Like [`Self::new`], but for a task whose future was handed a clone of
+    /// `token`. When the session is cleaned up, `token` is triggered and its
+    /// waiters are notified, and then the task is forcibly aborted straight
+    /// away; cleanup does not wait for the future (e.g. a service holding the
+    /// other clone through its [`AsyncMap`](crate::AsyncMap) channel) to
+    /// observe the token first.
+    pub(crate) fn new_with_cancellation_token(
+        session: Entity,
+        owner: Entity,
+        target: Entity,
+        task: BevyTask<Response>,
+        blocker: Option<Blocker>,
+        token: CancellationToken,
+    ) -> Self {
+        Self {
+            cancellation: Some(CancellationTokenStorage(token)),
+            ..Self::new(session, owner, target, task, blocker)
         }
     }
 }
@@ -149,6 +498,7 @@ impl<Response: 'static + Send + Sync> Operation for OperateTask<Response> {
                 let mut source_mut = world.entity_mut(source);
                 let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
                 let session = source_mut.get::<TaskSessionStorage>().or_broken()?.0;
+                let owner = source_mut.get::<TaskOwnerStorage>().or_broken()?.0;
                 let unblock = source_mut.take::<BlockerStorage>().or_broken()?;
                 if let Some(unblock) = unblock.0 {
                     roster.unblock(unblock);
@@ -156,6 +506,16 @@ impl<Response: 'static + Send + Sync> Operation for OperateTask<Response> {
 
                 world.entity_mut(target).give_input(session, result, roster);
                 world.despawn(source);
+
+                if let Some(mut owner_mut) = world.get_entity_mut(owner) {
+                    if let Some(mut tasks) = owner_mut.get_mut::<ActiveTasksStorage>() {
+                        tasks.list.retain(|task| task.task_id != source);
+                    }
+                }
+
+                if let Some(hook) = world.get::<TaskCompletionHook>(owner).map(|h| h.0) {
+                    hook(owner, world, roster);
+                }
             }
             Poll::Pending => {
                 // Task is still running
@@ -175,6 +535,16 @@ impl<Response: 'static + Send + Sync> Operation for OperateTask<Response> {
         let mut source_mut = clean.world.get_entity_mut(source).or_broken()?;
         let owner = source_mut.get::<TaskOwnerStorage>().or_broken()?.0;
         let task = source_mut.take::<TaskStorage<Response>>().or_broken()?.0;
+        if let Some(TaskCancelSignalStorage(signal)) = source_mut.get::<TaskCancelSignalStorage>() {
+            // Trigger the signal before the task gets forcibly aborted below;
+            // the future is not polled again in between.
+            signal.trigger();
+        }
+        if let Some(CancellationTokenStorage(token)) = source_mut.get::<CancellationTokenStorage>() {
+            // Wake up anything suspended on `CancellationToken::cancelled`
+            // before the task is forcibly aborted below.
+            token.trigger();
+        }
         let sender = clean.world.get_resource_or_insert_with(|| ChannelQueue::new()).sender.clone();
         AsyncComputeTaskPool::get().spawn(async move {
             task.cancel().await;
@@ -209,6 +579,10 @@ impl<Response: 'static + Send + Sync> Operation for OperateTask<Response> {
                 }
 
                 world.despawn(source);
+
+                if let Some(hook) = world.get::<TaskCompletionHook>(owner).map(|h| h.0) {
+                    hook(owner, world, roster);
+                }
             }));
         }).detach();
 
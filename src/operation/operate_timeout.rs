@@ -0,0 +1,159 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Operation, SingleTargetStorage, Service, ServiceRequest, SingleInputStorage,
+    dispatch_service, Cancel, OperationCleanup, OperationResult, OrBroken,
+    OperationSetup, OperationRequest, ActiveTasksStorage, OperationReachability,
+    ReachabilityResult, InputBundle, Input, ManageInput, OperationRoster,
+};
+
+use bevy::{
+    prelude::{Component, Entity, World, Query, App, Update},
+    ecs::{system::SystemState, world::Mut},
+};
+
+use std::{collections::HashMap, time::{Duration, Instant}};
+
+/// Wraps a [`Service`] so that a delivery is cancelled if it has not produced
+/// a response before `duration` elapses. Unlike [`OperateRetry`](crate::OperateRetry),
+/// the response is routed straight to the real `target`; this node only
+/// watches the clock and steps in with a [`Cancel::timed_out`] if the deadline
+/// is missed.
+pub(crate) struct OperateTimeout<Request, Response, Streams> {
+    provider: Entity,
+    duration: Duration,
+    target: Entity,
+    _ignore: std::marker::PhantomData<(Request, Response, Streams)>,
+}
+
+impl<Request: 'static + Send + Sync, Response, Streams> OperateTimeout<Request, Response, Streams> {
+    pub(crate) fn new(
+        provider: Service<Request, Response, Streams>,
+        duration: Duration,
+        target: Entity,
+    ) -> Self {
+        Self {
+            provider: provider.provider(),
+            duration,
+            target,
+            _ignore: Default::default(),
+        }
+    }
+}
+
+impl<Request: 'static + Send + Sync, Response, Streams> Operation for OperateTimeout<Request, Response, Streams> {
+    fn setup(self, OperationSetup { source, world }: OperationSetup) -> OperationResult {
+        world.get_entity_mut(self.target).or_broken()?
+            .insert(SingleInputStorage::new(source));
+
+        world.entity_mut(source).insert((
+            InputBundle::<Request>::new(),
+            ProviderStorage(self.provider),
+            DurationStorage(self.duration),
+            SingleTargetStorage(self.target),
+            DeadlineStorage::default(),
+            ActiveTasksStorage::default(),
+        ));
+        Ok(())
+    }
+
+    fn execute(
+        OperationRequest { source, world, roster }: OperationRequest
+    ) -> OperationResult {
+        let mut source_mut = world.get_entity_mut(source).or_broken()?;
+        let target = source_mut.get::<SingleTargetStorage>().or_broken()?.0;
+        let provider = source_mut.get::<ProviderStorage>().or_broken()?.0;
+        let duration = source_mut.get::<DurationStorage>().or_broken()?.0;
+        let Input { session, data: request } = source_mut.take_input::<Request>()?;
+
+        source_mut.get_mut::<DeadlineStorage>().or_broken()?
+            .deadlines.insert(session, Instant::now() + duration);
+        source_mut.give_input(session, request, roster)?;
+
+        dispatch_service(ServiceRequest {
+            provider,
+            target,
+            operation: OperationRequest { source, world, roster },
+        });
+        Ok(())
+    }
+
+    fn cleanup(mut clean: OperationCleanup) -> OperationResult {
+        if let Some(mut storage) = clean.world.get_mut::<DeadlineStorage>(clean.source) {
+            storage.deadlines.remove(&clean.session);
+        }
+        clean.cleanup_inputs::<Request>()?;
+        ActiveTasksStorage::cleanup(clean)
+    }
+
+    fn is_reachable(reachability: OperationReachability) -> ReachabilityResult {
+        if reachability.has_input::<Request>()? {
+            return Ok(true);
+        }
+        if ActiveTasksStorage::contains_session(reachability)? {
+            return Ok(true);
+        }
+        SingleInputStorage::is_reachable(reachability)
+    }
+}
+
+#[derive(Component)]
+struct ProviderStorage(Entity);
+
+#[derive(Component)]
+struct DurationStorage(Duration);
+
+#[derive(Component, Default)]
+struct DeadlineStorage {
+    deadlines: HashMap<Entity, Instant>,
+}
+
+/// Sweep every outstanding `.timeout(~)` delivery and cancel any session whose
+/// deadline has elapsed. This is meant to run once per update, the same way
+/// [`cancel_service`](crate::cancel_service) reacts to providers disappearing.
+pub(crate) fn check_timed_out_deliveries(world: &mut World, roster: &mut OperationRoster) {
+    let mut state: SystemState<Query<(Entity, &DeadlineStorage)>> = SystemState::new(world);
+    let now = Instant::now();
+
+    let mut expired = Vec::new();
+    for (source, deadlines) in state.get(world).iter() {
+        for (session, deadline) in &deadlines.deadlines {
+            if *deadline <= now {
+                expired.push((source, *session));
+            }
+        }
+    }
+
+    for (source, session) in expired {
+        roster.cancel(Cancel::timed_out(source, session));
+        if let Some(mut deadlines) = world.get_mut::<DeadlineStorage>(source) {
+            deadlines.deadlines.remove(&session);
+        }
+    }
+}
+
+/// Register [`check_timed_out_deliveries`] to run once per update. Without
+/// this, a `.timeout(~)` deadline is recorded into [`DeadlineStorage`] but
+/// never swept, so it would never actually cancel anything.
+pub(crate) fn register_timeout_sweep(app: &mut App) {
+    app.add_systems(Update, |world: &mut World| {
+        world.resource_scope(|world, mut roster: Mut<OperationRoster>| {
+            check_timed_out_deliveries(world, &mut roster);
+        });
+    });
+}
@@ -31,6 +31,33 @@ pub use blocking::*;
 mod builder;
 pub use builder::ServiceBuilder;
 
+mod retry;
+pub use retry::*;
+
+mod cache;
+pub use cache::*;
+
+mod supervise;
+pub use supervise::*;
+
+mod timeout;
+pub use timeout::*;
+
+mod concurrency_limit;
+pub use concurrency_limit::*;
+
+mod load_shed;
+pub use load_shed::*;
+
+mod filter;
+pub use filter::*;
+
+mod access_control;
+pub use access_control::*;
+
+mod fallback;
+pub use fallback::*;
+
 pub(crate) mod delivery;
 pub(crate) use delivery::*;
 
@@ -0,0 +1,81 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateAccessControl, PermissionCheck,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Gate this service behind a [`PermissionCheck`], casbin-style: before a
+    /// request is dispatched, `checker.enforce(actor, object, action)` is
+    /// consulted, where `actor` is borrowed from the request via
+    /// `Request: AsRef<Actor>`. Denied requests are cancelled with
+    /// [`Cancel::unauthorized`](crate::Cancel) instead of ever reaching this
+    /// service.
+    pub fn require_permission<C, Actor>(
+        self,
+        checker: C,
+        object: impl Into<String>,
+        action: impl Into<String>,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams>
+    where
+        C: PermissionCheck<Actor>,
+        Request: AsRef<Actor>,
+        Actor: 'static + Send + Sync,
+    {
+        AccessControlProvider {
+            inner: self,
+            checker,
+            object: object.into(),
+            action: action.into(),
+        }
+    }
+}
+
+struct AccessControlProvider<Request, Response, Streams, C> {
+    inner: Service<Request, Response, Streams>,
+    checker: C,
+    object: String,
+    action: String,
+}
+
+impl<Request, Response, Streams, C, Actor> Provider for AccessControlProvider<Request, Response, Streams, C>
+where
+    Request: 'static + Send + Sync + AsRef<Actor>,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+    C: PermissionCheck<Actor>,
+    Actor: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateAccessControl::new(self.inner, self.checker, self.object, self.action, target),
+        ));
+    }
+}
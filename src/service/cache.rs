@@ -0,0 +1,156 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateCache, Storage,
+};
+
+use bevy::prelude::{Component, Entity, Commands};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Memoizes the responses of a [`Service`] keyed by request, attached to
+/// whichever entity is passed into [`Service::cached`]. Installed
+/// automatically the first time a `.cached(cache)` wrapper runs against that
+/// entity; you can also insert [`Cache::with_capacity`] yourself beforehand
+/// if you want bounded, LRU-evicting storage instead of the unbounded
+/// default.
+#[derive(Component)]
+pub struct Cache<Request, Response> {
+    capacity: Option<usize>,
+    entries: HashMap<Request, Storage<Response>>,
+    order: VecDeque<Request>,
+    in_flight: HashMap<Request, Vec<(Entity, Entity)>>,
+}
+
+impl<Request: Clone + Eq + Hash, Response> Default for Cache<Request, Response> {
+    fn default() -> Self {
+        Self {
+            capacity: None,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            in_flight: HashMap::new(),
+        }
+    }
+}
+
+impl<Request: Clone + Eq + Hash, Response> Cache<Request, Response> {
+    /// Create a cache that evicts its least-recently-inserted entry once
+    /// more than `capacity` distinct requests have been memoized.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity: Some(capacity), ..Default::default() }
+    }
+
+    /// How many completed entries are currently memoized.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn hit(&self, request: &Request) -> Option<Response>
+    where
+        Response: Clone,
+    {
+        self.entries.get(request).map(|stored| stored.data.clone())
+    }
+
+    pub(crate) fn is_in_flight(&self, request: &Request) -> bool {
+        self.in_flight.contains_key(request)
+    }
+
+    /// Register `(session, target)` as waiting on the response for `request`,
+    /// whether it is the dispatch that originates the in-flight attempt or a
+    /// later request that is piggy-backing on it.
+    pub(crate) fn add_waiter(&mut self, request: Request, session: Entity, target: Entity) {
+        self.in_flight.entry(request).or_insert_with(Vec::new).push((session, target));
+    }
+
+    /// Record the response produced by `session` for `request`, returning
+    /// every `(session, target)` pair that was waiting on this key so the
+    /// caller can deliver the response to each of them.
+    pub(crate) fn complete(
+        &mut self,
+        request: Request,
+        session: Entity,
+        response: Response,
+    ) -> Vec<(Entity, Entity)> {
+        let waiters = self.in_flight.remove(&request).unwrap_or_default();
+
+        if self.entries.contains_key(&request) {
+            self.order.retain(|r| r != &request);
+        }
+        self.order.push_back(request.clone());
+        self.entries.insert(request, Storage { data: response, session });
+
+        if let Some(capacity) = self.capacity {
+            while self.entries.len() > capacity {
+                let Some(oldest) = self.order.pop_front() else { break };
+                self.entries.remove(&oldest);
+            }
+        }
+
+        waiters
+    }
+}
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone + Eq + Hash,
+    Response: 'static + Send + Sync + Clone,
+    Streams: 'static + Send + Sync,
+{
+    /// Memoize responses from this service in a [`Cache`] kept on `cache`.
+    /// A request whose key already has a stored response is answered
+    /// immediately without running the service again; a request whose key
+    /// is already in flight attaches to that attempt and receives a clone of
+    /// its eventual response instead of starting a duplicate run.
+    pub fn cached(
+        self,
+        cache: Entity,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        CacheProvider { inner: self, cache }
+    }
+}
+
+struct CacheProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+    cache: Entity,
+}
+
+impl<Request, Response, Streams> Provider for CacheProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone + Eq + Hash,
+    Response: 'static + Send + Sync + Clone,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateCache::new(self.inner, self.cache, target),
+        ));
+    }
+}
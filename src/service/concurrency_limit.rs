@@ -0,0 +1,68 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateService, InitConcurrencyLimit,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Cap how many requests this provider will run at the same time, across
+    /// every delivery label. Unlike [`DeliveryInstructions`](crate::DeliveryInstructions)
+    /// labels, which only serialize requests that share a label, this limits
+    /// the provider as a whole, the same way tower's `ConcurrencyLimit` does.
+    ///
+    /// The permit pool lives on the provider entity itself, so every request
+    /// to this provider -- no matter which node dispatches it -- draws from
+    /// the same shared pool of `max` permits.
+    pub fn concurrency_limit(
+        self,
+        max: usize,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        ConcurrencyLimitProvider { inner: self, max }
+    }
+}
+
+struct ConcurrencyLimitProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+    max: usize,
+}
+
+impl<Request, Response, Streams> Provider for ConcurrencyLimitProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(InitConcurrencyLimit::new(self.inner.provider(), self.max));
+        commands.add(AddOperation::new(
+            source,
+            OperateService::new(self.inner, target),
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateService,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Declare a backup provider to fall back on, like actix-web's default
+    /// service for unmatched routes. If this service ever becomes
+    /// unavailable (e.g. despawned or missing a critical component), deliveries
+    /// that depended on it are re-routed to `fallback` instead of being
+    /// cancelled with [`Cancel::service_unavailable`](crate::Cancel).
+    pub fn or_else(
+        self,
+        fallback: Service<Request, Response, Streams>,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        FallbackProvider { inner: self, fallback }
+    }
+}
+
+struct FallbackProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+    fallback: Service<Request, Response, Streams>,
+}
+
+impl<Request, Response, Streams> Provider for FallbackProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateService::with_fallback(self.inner, self.fallback, target),
+        ));
+    }
+}
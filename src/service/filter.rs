@@ -0,0 +1,65 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateFilter, FilterPredicate,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Gate delivery to this service behind a [`FilterPredicate`], in the
+    /// spirit of `tower-filter`. The predicate inspects (and may transform)
+    /// each request; an `Err` cancels the delivery with a
+    /// [`Filtered`](crate::Filtered) cancellation instead of letting it reach
+    /// this service.
+    pub fn filter<F: FilterPredicate<Request>>(
+        self,
+        predicate: F,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        FilterProvider { inner: self, predicate }
+    }
+}
+
+struct FilterProvider<Request, Response, Streams, F> {
+    inner: Service<Request, Response, Streams>,
+    predicate: F,
+}
+
+impl<Request, Response, Streams, F> Provider for FilterProvider<Request, Response, Streams, F>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+    F: FilterPredicate<Request>,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateFilter::new(self.inner, self.predicate, target),
+        ));
+    }
+}
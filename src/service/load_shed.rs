@@ -0,0 +1,64 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateService, InitLoadShed,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Pair this with [`Self::concurrency_limit`] to reject new requests
+    /// instead of queueing them once the provider is already running at its
+    /// configured limit. The rejected request is immediately cancelled with
+    /// [`Cancel::service_unavailable`](crate::Cancel), the same cancellation
+    /// that fires when the provider disappears outright, so callers can
+    /// fall back or retry elsewhere without waiting in line.
+    pub fn load_shed(
+        self,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        LoadShedProvider { inner: self }
+    }
+}
+
+struct LoadShedProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+}
+
+impl<Request, Response, Streams> Provider for LoadShedProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(InitLoadShed::new(self.inner.provider()));
+        commands.add(AddOperation::new(
+            source,
+            OperateService::new(self.inner, target),
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateRetry,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+/// A policy that decides whether a [`Service`] response should be retried.
+///
+/// Implement this the same way you would implement a `tower`/`burger` retry
+/// policy: inspect the response that came back and either accept it or hand
+/// back a (possibly modified) request to try again.
+pub trait RetryPolicy<Request, Response>: 'static + Send + Sync {
+    /// Per-attempt state that the policy needs to remember between retries,
+    /// e.g. an attempt counter or a backoff schedule.
+    type State: 'static + Send + Sync;
+
+    /// Create the initial state for a fresh request.
+    fn init_state(&self) -> Self::State;
+
+    /// Inspect the outcome of an attempt. Return `Some(next_request)` to retry
+    /// with a new (possibly mutated) request, or `None` to accept `res` as the
+    /// final response.
+    fn on_response(
+        &self,
+        state: &mut Self::State,
+        req: &Request,
+        res: &Response,
+    ) -> Option<Request>;
+}
+
+/// A built-in [`RetryPolicy`] that retries a fixed number of times and then
+/// gives up, passing along whatever response came back last.
+pub struct FiniteRetries {
+    pub max: usize,
+}
+
+impl FiniteRetries {
+    pub fn new(max: usize) -> Self {
+        Self { max }
+    }
+}
+
+/// State used by [`FiniteRetries`] to count how many attempts have been made.
+pub struct FiniteRetriesState {
+    attempted: usize,
+}
+
+impl<Request: Clone, Response> RetryPolicy<Request, Response> for FiniteRetries {
+    type State = FiniteRetriesState;
+
+    fn init_state(&self) -> Self::State {
+        FiniteRetriesState { attempted: 0 }
+    }
+
+    fn on_response(
+        &self,
+        state: &mut Self::State,
+        req: &Request,
+        _res: &Response,
+    ) -> Option<Request> {
+        state.attempted += 1;
+        if state.attempted >= self.max {
+            return None;
+        }
+
+        Some(req.clone())
+    }
+}
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Wrap this service with retry behavior. Whenever a response comes back,
+    /// `policy` is consulted to decide whether the request should be
+    /// re-dispatched to this same service or whether the response should be
+    /// forwarded downstream as-is.
+    pub fn retry<P: RetryPolicy<Request, Response>>(
+        self,
+        policy: P,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        RetryProvider { inner: self, policy }
+    }
+}
+
+struct RetryProvider<Request, Response, Streams, P> {
+    inner: Service<Request, Response, Streams>,
+    policy: P,
+}
+
+impl<Request, Response, Streams, P> Provider for RetryProvider<Request, Response, Streams, P>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+    P: RetryPolicy<Request, Response>,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateRetry::new(self.inner, self.policy, target),
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateSupervise, CancellationCause,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+use std::time::Duration;
+
+/// How long to wait before re-injecting a cancelled delivery under a
+/// [`SupervisionPolicy`].
+#[derive(Debug, Clone, Copy)]
+pub enum SupervisionBackoff {
+    /// Always wait the same duration between attempts.
+    Fixed(Duration),
+    /// Double the wait on each attempt, starting at `base` and never
+    /// exceeding `cap`.
+    Exponential { base: Duration, cap: Duration },
+}
+
+impl SupervisionBackoff {
+    pub(crate) fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        match *self {
+            SupervisionBackoff::Fixed(delay) => delay,
+            SupervisionBackoff::Exponential { base, cap } => {
+                let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+                base.saturating_mul(factor).min(cap)
+            }
+        }
+    }
+}
+
+/// A supervision-tree-style restart policy for [`Service::on_cancel`]: when a
+/// delivery is cancelled, `matches` decides whether the policy takes
+/// responsibility for the cause. If it does, the original request is
+/// re-injected into the supervised service instead of letting the
+/// cancellation cascade downstream, up to `max_attempts` times with
+/// [`SupervisionBackoff`] delay between attempts. Causes that `matches`
+/// rejects are forwarded immediately; once attempts are exhausted the final
+/// cause is forwarded wrapped in
+/// [`CancellationCause::RetriesExhausted`](crate::RetriesExhausted).
+pub struct SupervisionPolicy {
+    pub max_attempts: u32,
+    pub backoff: SupervisionBackoff,
+    matches: fn(&CancellationCause) -> bool,
+}
+
+impl SupervisionPolicy {
+    pub fn new(
+        max_attempts: u32,
+        backoff: SupervisionBackoff,
+        matches: fn(&CancellationCause) -> bool,
+    ) -> Self {
+        Self { max_attempts, backoff, matches }
+    }
+
+    pub fn fixed(
+        max_attempts: u32,
+        delay: Duration,
+        matches: fn(&CancellationCause) -> bool,
+    ) -> Self {
+        Self::new(max_attempts, SupervisionBackoff::Fixed(delay), matches)
+    }
+
+    pub fn exponential(
+        max_attempts: u32,
+        base: Duration,
+        cap: Duration,
+        matches: fn(&CancellationCause) -> bool,
+    ) -> Self {
+        Self::new(max_attempts, SupervisionBackoff::Exponential { base, cap }, matches)
+    }
+
+    pub(crate) fn matches(&self, cause: &CancellationCause) -> bool {
+        (self.matches)(cause)
+    }
+}
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Wrap this service with a [`SupervisionPolicy`]. Whenever a delivery
+    /// through this service is cancelled, the policy decides whether to
+    /// re-inject the original request (recovering from the cancellation) or
+    /// let it cascade downstream as usual.
+    pub fn on_cancel(
+        self,
+        policy: SupervisionPolicy,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        SuperviseProvider { inner: self, policy }
+    }
+}
+
+struct SuperviseProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+    policy: SupervisionPolicy,
+}
+
+impl<Request, Response, Streams> Provider for SuperviseProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync + Clone,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateSupervise::new(self.inner, self.policy, target),
+        ));
+    }
+}
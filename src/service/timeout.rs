@@ -0,0 +1,64 @@
+/*
+ * Copyright (C) 2024 Open Source Robotics Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ *
+*/
+
+use crate::{
+    Service, AddOperation, Provider, OperateTimeout,
+};
+
+use bevy::prelude::{Entity, Commands};
+
+use std::time::Duration;
+
+impl<Request, Response, Streams> Service<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    /// Wrap this service so that a delivery is cancelled with
+    /// [`TimedOut`](crate::TimedOut) if it has not produced a response within
+    /// `duration`.
+    pub fn timeout(
+        self,
+        duration: Duration,
+    ) -> impl Provider<Request = Request, Response = Response, Streams = Streams> {
+        TimeoutProvider { inner: self, duration }
+    }
+}
+
+struct TimeoutProvider<Request, Response, Streams> {
+    inner: Service<Request, Response, Streams>,
+    duration: Duration,
+}
+
+impl<Request, Response, Streams> Provider for TimeoutProvider<Request, Response, Streams>
+where
+    Request: 'static + Send + Sync,
+    Response: 'static + Send + Sync,
+    Streams: 'static + Send + Sync,
+{
+    type Request = Request;
+    type Response = Response;
+    type Streams = Streams;
+
+    fn connect(self, source: Entity, target: Entity, commands: &mut Commands) {
+        commands.add(AddOperation::new(
+            source,
+            OperateTimeout::new(self.inner, self.duration, target),
+        ));
+    }
+}
@@ -15,9 +15,14 @@
  *
 */
 
-use bevy::prelude::{Component, Bundle, Entity, Commands, World, BuildChildren};
+use bevy::{
+    prelude::{Component, Bundle, Entity, Commands, World, BuildChildren},
+    ecs::system::Command,
+};
+
+use crossbeam::channel::{Receiver, unbounded, bounded};
 
-use crossbeam::channel::{Receiver, unbounded};
+use smallvec::SmallVec;
 
 use std::sync::Arc;
 
@@ -27,11 +32,35 @@ use crate::{
     InnerChannel, TakenStream, StreamChannel, OperationError,
 };
 
-pub trait Stream: 'static + Send + Sync + Sized {
+pub trait Stream: 'static + Send + Sync + Sized + Clone {
     fn send(
         self,
-        StreamRequest { session, target, world, roster, .. }: StreamRequest
+        StreamRequest { source, session, target, world, roster, .. }: StreamRequest
     ) -> OperationResult {
+        if let Some(dataspace) = world.get::<StreamDataspace<Self>>(target) {
+            let matching: SmallVec<[Entity; 8]> = dataspace.subscriptions.iter()
+                .filter(|subscription| (subscription.predicate)(&self))
+                .map(|subscription| subscription.subscriber)
+                .collect();
+
+            for subscriber in matching {
+                world.get_entity_mut(subscriber).or_broken()?
+                    .give_input(session, self.clone(), roster)?;
+            }
+
+            return Ok(());
+        }
+
+        if world.get_entity(target).is_none() || world.get::<UnusedTarget>(target).is_some() {
+            let optional = world.get::<StreamAvailable<Self>>(source)
+                .is_some_and(|available| available.availability == StreamAvailability::Optional);
+            if optional {
+                // Nothing is listening to this Optional stream; that is
+                // legal, so there is nothing left to do.
+                return Ok(());
+            }
+        }
+
         world.get_entity_mut(target).or_broken()?.give_input(session, self, roster)
     }
 
@@ -101,6 +130,38 @@ pub trait Stream: 'static + Send + Sync + Sized {
             receiver,
         )
     }
+
+    /// Like [`Self::spawn_request_stream`], but the backing channel is
+    /// bounded to `capacity` items instead of growing without limit. Use this
+    /// for a long-running streaming node whose consumer might fall behind a
+    /// fast producer.
+    ///
+    /// Producer-side backpressure once the channel fills up is applied by the
+    /// [`StreamChannel`]/[`InnerChannel`] send path that [`TakenStream`] hands
+    /// the sender half to: an [`AsyncMap`](crate::AsyncMap) producer's send
+    /// future suspends until capacity frees up, while a blocking producer
+    /// gets a try-send that surfaces a "would block" error instead.
+    fn spawn_bounded_request_stream(
+        session: Entity,
+        capacity: usize,
+        commands: &mut Commands,
+    ) -> (
+        StreamTargetStorage<Self>,
+        Receiver<Self>,
+    ) {
+        let (sender, receiver) = bounded::<Self>(capacity);
+        let target = commands
+            .spawn(())
+            .set_parent(session)
+            .id();
+
+        commands.add(AddOperation::new(target, TakenStream::new(sender)));
+
+        (
+            StreamTargetStorage::new(target),
+            receiver,
+        )
+    }
 }
 
 pub struct StreamRequest<'a> {
@@ -116,16 +177,45 @@ pub struct StreamRequest<'a> {
     pub roster: &'a mut OperationRoster,
 }
 
+/// Whether a [`Stream`] offered by a node must have a consumer wired up
+/// downstream. A [`Required`](Self::Required) stream that ends up
+/// unconnected is reported by [`validate_required_stream`], which nothing in
+/// this crate runs during a workflow build;
+/// an [`Optional`](Self::Optional) stream is allowed to go unconnected, and
+/// [`Stream::send`] silently drops items sent to an unconnected target
+/// instead of raising an [`OperationError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StreamAvailability {
+    #[default]
+    Required,
+    Optional,
+}
+
 /// [`StreamAvailable`] is a marker component that indicates what streams are offered by
-/// a service.
+/// a service, along with the [`StreamAvailability`] the node is declaring for it.
 #[derive(Component)]
 pub struct StreamAvailable<T: Stream> {
+    availability: StreamAvailability,
     _ignore: std::marker::PhantomData<T>,
 }
 
+impl<T: Stream> StreamAvailable<T> {
+    pub fn required() -> Self {
+        Self { availability: StreamAvailability::Required, _ignore: Default::default() }
+    }
+
+    pub fn optional() -> Self {
+        Self { availability: StreamAvailability::Optional, _ignore: Default::default() }
+    }
+
+    pub fn availability(&self) -> StreamAvailability {
+        self.availability
+    }
+}
+
 impl<T: Stream> Default for StreamAvailable<T> {
     fn default() -> Self {
-        Self { _ignore: Default::default() }
+        Self::required()
     }
 }
 
@@ -146,6 +236,121 @@ impl<T: Stream> StreamTargetStorage<T> {
     }
 }
 
+/// A [`StreamAvailability::Required`] stream that [`validate_required_stream`]
+/// found to have no consumer wired up to its [`StreamTargetStorage`] target.
+#[derive(Debug)]
+pub struct UnsatisfiedRequiredStream {
+    pub node: Entity,
+    pub stream: &'static str,
+}
+
+/// Confirm that the `T` stream offered by `node`, if declared
+/// [`StreamAvailability::Required`] via its [`StreamAvailable<T>`], has been
+/// connected to a consumer. A stream is considered connected once its
+/// [`StreamTargetStorage`] target is no longer an [`UnusedTarget`]
+/// placeholder. Streams with no [`StreamAvailable<T>`] at all, or declared
+/// [`StreamAvailability::Optional`], are always considered satisfied.
+pub fn validate_required_stream<T: Stream>(
+    node: Entity,
+    world: &World,
+) -> Result<(), UnsatisfiedRequiredStream> {
+    let Some(available) = world.get::<StreamAvailable<T>>(node) else {
+        return Ok(());
+    };
+
+    if available.availability != StreamAvailability::Required {
+        return Ok(());
+    }
+
+    let Some(target_storage) = world.get::<StreamTargetStorage<T>>(node) else {
+        return Ok(());
+    };
+
+    if world.get::<UnusedTarget>(target_storage.get()).is_some() {
+        return Err(UnsatisfiedRequiredStream {
+            node,
+            stream: std::any::type_name::<T>(),
+        });
+    }
+
+    Ok(())
+}
+
+/// One subscription inside a [`StreamDataspace`]: `subscriber` receives a
+/// clone of every item for which `predicate` returns true.
+struct DataspaceSubscription<T> {
+    subscriber: Entity,
+    predicate: Box<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+/// A content-routed stream target, inspired by a Syndicate dataspace: instead
+/// of forwarding every item to one fixed [`StreamTargetStorage`] target, a
+/// [`StreamDataspace`] holds a set of subscribers that each only receive the
+/// items matching their own predicate. Subscriptions are asserted and
+/// retracted at runtime with [`AssertStreamSubscription`] and
+/// [`RetractStreamSubscription`]; once retracted, no further items are routed
+/// to that subscriber.
+#[derive(Component)]
+pub struct StreamDataspace<T: Stream> {
+    subscriptions: Vec<DataspaceSubscription<T>>,
+}
+
+impl<T: Stream> Default for StreamDataspace<T> {
+    fn default() -> Self {
+        Self { subscriptions: Vec::new() }
+    }
+}
+
+/// Command to assert a new subscription into a [`StreamDataspace`] target.
+pub struct AssertStreamSubscription<T: Stream> {
+    dataspace: Entity,
+    subscriber: Entity,
+    predicate: Box<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<T: Stream> AssertStreamSubscription<T> {
+    pub fn new(
+        dataspace: Entity,
+        subscriber: Entity,
+        predicate: impl Fn(&T) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self { dataspace, subscriber, predicate: Box::new(predicate) }
+    }
+}
+
+impl<T: Stream> Command for AssertStreamSubscription<T> {
+    fn apply(self, world: &mut World) {
+        if let Some(mut dataspace) = world.get_mut::<StreamDataspace<T>>(self.dataspace) {
+            dataspace.subscriptions.push(DataspaceSubscription {
+                subscriber: self.subscriber,
+                predicate: self.predicate,
+            });
+        }
+    }
+}
+
+/// Command to retract every subscription that `subscriber` holds in a
+/// [`StreamDataspace`] target.
+pub struct RetractStreamSubscription<T: Stream> {
+    dataspace: Entity,
+    subscriber: Entity,
+    _ignore: std::marker::PhantomData<T>,
+}
+
+impl<T: Stream> RetractStreamSubscription<T> {
+    pub fn new(dataspace: Entity, subscriber: Entity) -> Self {
+        Self { dataspace, subscriber, _ignore: Default::default() }
+    }
+}
+
+impl<T: Stream> Command for RetractStreamSubscription<T> {
+    fn apply(self, world: &mut World) {
+        if let Some(mut dataspace) = world.get_mut::<StreamDataspace<T>>(self.dataspace) {
+            dataspace.subscriptions.retain(|subscription| subscription.subscriber != self.subscriber);
+        }
+    }
+}
+
 pub trait StreamPack: 'static + Send + Sync {
     type StreamAvailableBundle: Bundle + Default;
     type StreamStorageBundle: Bundle;
@@ -153,6 +358,7 @@ pub trait StreamPack: 'static + Send + Sync {
     type StreamOutputPack;
     type Receiver;
     type Channel;
+    type DataspaceOutputPack;
 
     fn spawn_scope_streams(scope: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -169,11 +375,36 @@ pub trait StreamPack: 'static + Send + Sync {
         Self::StreamOutputPack,
     );
 
+    /// Spawn a content-routed [`StreamDataspace`] target for this pack so a
+    /// node can advertise a dataspace stream output that multiple downstream
+    /// workflows can subscribe to independently, without being wired into the
+    /// workflow graph at build time.
+    fn spawn_node_dataspace_streams(commands: &mut Commands) -> Self::DataspaceOutputPack;
+
+    /// Collect an [`UnsatisfiedRequiredStream`] for every stream in this pack
+    /// that `node` declared [`StreamAvailability::Required`] but never got
+    /// connected to a consumer. Every violation is collected instead of
+    /// stopping at the first one. Nothing in this crate calls this while
+    /// building a workflow.
+    fn validate_required_streams(
+        node: Entity,
+        world: &World,
+        errors: &mut Vec<UnsatisfiedRequiredStream>,
+    );
+
     fn make_receiver(session: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
     );
 
+    /// Like [`Self::make_receiver`], but backed by a bounded channel holding
+    /// at most `capacity` items per stream, so a fast producer cannot grow the
+    /// queue without limit when the consumer falls behind.
+    fn make_bounded_receiver(session: Entity, capacity: usize, commands: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    );
+
     fn make_channel(inner: &Arc<InnerChannel>, world: &World) -> Result<Self::Channel, OperationError>;
 }
 
@@ -184,6 +415,7 @@ impl<T: Stream> StreamPack for T {
     type StreamOutputPack = Output<Self>;
     type Receiver = Receiver<Self>;
     type Channel = StreamChannel<Self>;
+    type DataspaceOutputPack = Entity;
 
     fn spawn_scope_streams(scope: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -206,6 +438,20 @@ impl<T: Stream> StreamPack for T {
         T::spawn_node_stream(scope, commands)
     }
 
+    fn spawn_node_dataspace_streams(commands: &mut Commands) -> Self::DataspaceOutputPack {
+        commands.spawn(StreamDataspace::<Self>::default()).id()
+    }
+
+    fn validate_required_streams(
+        node: Entity,
+        world: &World,
+        errors: &mut Vec<UnsatisfiedRequiredStream>,
+    ) {
+        if let Err(error) = validate_required_stream::<Self>(node, world) {
+            errors.push(error);
+        }
+    }
+
     fn make_receiver(session: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
@@ -213,6 +459,13 @@ impl<T: Stream> StreamPack for T {
         Self::spawn_request_stream(session, commands)
     }
 
+    fn make_bounded_receiver(session: Entity, capacity: usize, commands: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    ) {
+        Self::spawn_bounded_request_stream(session, capacity, commands)
+    }
+
     fn make_channel(
         inner: &Arc<InnerChannel>,
         world: &World,
@@ -229,6 +482,7 @@ impl StreamPack for () {
     type StreamOutputPack = ();
     type Receiver = ();
     type Channel = ();
+    type DataspaceOutputPack = ();
 
     fn spawn_scope_streams(_: Entity, _: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -251,6 +505,14 @@ impl StreamPack for () {
         ((), ())
     }
 
+    fn spawn_node_dataspace_streams(_: &mut Commands) -> Self::DataspaceOutputPack {
+        ()
+    }
+
+    fn validate_required_streams(_: Entity, _: &World, _: &mut Vec<UnsatisfiedRequiredStream>) {
+        // No streams to validate.
+    }
+
     fn make_receiver(_: Entity, _: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
@@ -258,6 +520,13 @@ impl StreamPack for () {
         ((), ())
     }
 
+    fn make_bounded_receiver(_: Entity, _: usize, _: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    ) {
+        ((), ())
+    }
+
     fn make_channel(
         _: &Arc<InnerChannel>,
         _: &World,
@@ -273,6 +542,7 @@ impl<T1: StreamPack> StreamPack for (T1,) {
     type StreamOutputPack = T1::StreamOutputPack;
     type Receiver = T1::Receiver;
     type Channel = T1::Channel;
+    type DataspaceOutputPack = T1::DataspaceOutputPack;
 
     fn spawn_scope_streams(scope: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -295,6 +565,18 @@ impl<T1: StreamPack> StreamPack for (T1,) {
         T1::spawn_node_streams(scope, commands)
     }
 
+    fn spawn_node_dataspace_streams(commands: &mut Commands) -> Self::DataspaceOutputPack {
+        T1::spawn_node_dataspace_streams(commands)
+    }
+
+    fn validate_required_streams(
+        node: Entity,
+        world: &World,
+        errors: &mut Vec<UnsatisfiedRequiredStream>,
+    ) {
+        T1::validate_required_streams(node, world, errors);
+    }
+
     fn make_receiver(session: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
@@ -302,6 +584,13 @@ impl<T1: StreamPack> StreamPack for (T1,) {
         T1::make_receiver(session, commands)
     }
 
+    fn make_bounded_receiver(session: Entity, capacity: usize, commands: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    ) {
+        T1::make_bounded_receiver(session, capacity, commands)
+    }
+
     fn make_channel(
         inner: &Arc<InnerChannel>,
         world: &World,
@@ -317,6 +606,7 @@ impl<T1: StreamPack, T2: StreamPack> StreamPack for (T1, T2) {
     type StreamOutputPack = (T1::StreamOutputPack, T2::StreamOutputPack);
     type Receiver = (T1::Receiver, T2::Receiver);
     type Channel = (T1::Channel, T2::Channel);
+    type DataspaceOutputPack = (T1::DataspaceOutputPack, T2::DataspaceOutputPack);
 
     fn spawn_scope_streams(scope: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -345,6 +635,21 @@ impl<T1: StreamPack, T2: StreamPack> StreamPack for (T1, T2) {
         ((t1.0, t2.0), (t1.1, t2.1))
     }
 
+    fn spawn_node_dataspace_streams(commands: &mut Commands) -> Self::DataspaceOutputPack {
+        let t1 = T1::spawn_node_dataspace_streams(commands);
+        let t2 = T2::spawn_node_dataspace_streams(commands);
+        (t1, t2)
+    }
+
+    fn validate_required_streams(
+        node: Entity,
+        world: &World,
+        errors: &mut Vec<UnsatisfiedRequiredStream>,
+    ) {
+        T1::validate_required_streams(node, world, errors);
+        T2::validate_required_streams(node, world, errors);
+    }
+
     fn make_receiver(session: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
@@ -354,6 +659,15 @@ impl<T1: StreamPack, T2: StreamPack> StreamPack for (T1, T2) {
         ((t1.0, t2.0), (t1.1, t2.1))
     }
 
+    fn make_bounded_receiver(session: Entity, capacity: usize, commands: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    ) {
+        let t1 = T1::make_bounded_receiver(session, capacity, commands);
+        let t2 = T2::make_bounded_receiver(session, capacity, commands);
+        ((t1.0, t2.0), (t1.1, t2.1))
+    }
+
     fn make_channel(
         inner: &Arc<InnerChannel>,
         world: &World,
@@ -371,6 +685,7 @@ impl<T1: StreamPack, T2: StreamPack, T3: StreamPack> StreamPack for (T1, T2, T3)
     type StreamOutputPack = (T1::StreamOutputPack, T2::StreamOutputPack, T3::StreamOutputPack);
     type Receiver = (T1::Receiver, T2::Receiver, T3::Receiver);
     type Channel = (T1::Channel, T2::Channel, T3::Channel);
+    type DataspaceOutputPack = (T1::DataspaceOutputPack, T2::DataspaceOutputPack, T3::DataspaceOutputPack);
 
     fn spawn_scope_streams(scope: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
@@ -402,6 +717,23 @@ impl<T1: StreamPack, T2: StreamPack, T3: StreamPack> StreamPack for (T1, T2, T3)
         ((t1.0, t2.0, t3.0), (t1.1, t2.1, t3.1))
     }
 
+    fn spawn_node_dataspace_streams(commands: &mut Commands) -> Self::DataspaceOutputPack {
+        let t1 = T1::spawn_node_dataspace_streams(commands);
+        let t2 = T2::spawn_node_dataspace_streams(commands);
+        let t3 = T3::spawn_node_dataspace_streams(commands);
+        (t1, t2, t3)
+    }
+
+    fn validate_required_streams(
+        node: Entity,
+        world: &World,
+        errors: &mut Vec<UnsatisfiedRequiredStream>,
+    ) {
+        T1::validate_required_streams(node, world, errors);
+        T2::validate_required_streams(node, world, errors);
+        T3::validate_required_streams(node, world, errors);
+    }
+
     fn make_receiver(session: Entity, commands: &mut Commands) -> (
         Self::StreamStorageBundle,
         Self::Receiver,
@@ -412,6 +744,16 @@ impl<T1: StreamPack, T2: StreamPack, T3: StreamPack> StreamPack for (T1, T2, T3)
         ((t1.0, t2.0, t3.0), (t1.1, t2.1, t3.1))
     }
 
+    fn make_bounded_receiver(session: Entity, capacity: usize, commands: &mut Commands) -> (
+        Self::StreamStorageBundle,
+        Self::Receiver,
+    ) {
+        let t1 = T1::make_bounded_receiver(session, capacity, commands);
+        let t2 = T2::make_bounded_receiver(session, capacity, commands);
+        let t3 = T3::make_bounded_receiver(session, capacity, commands);
+        ((t1.0, t2.0, t3.0), (t1.1, t2.1, t3.1))
+    }
+
     fn make_channel(
         inner: &Arc<InnerChannel>,
         world: &World,